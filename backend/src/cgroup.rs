@@ -0,0 +1,176 @@
+//! Helpers for operating on cgroup v2 directories.
+
+use crate::types::ProcError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::{thread, time};
+
+/// How long to wait for the kernel to report a freeze or thaw as complete.
+const FREEZE_TIMEOUT: time::Duration = time::Duration::from_secs(2);
+
+/// Find where the cgroup2 hierarchy is mounted by scanning `/proc/self/mountinfo`.
+pub(crate) fn mount_point() -> Result<PathBuf, ProcError> {
+    let mountinfo = fs::read_to_string("/proc/self/mountinfo")
+        .map_err(|e| ProcError::CgroupError(format!("Failed to read mountinfo: {}", e)))?;
+
+    for line in mountinfo.lines() {
+        // Optional fields end with a lone "-", followed by the filesystem type
+        let Some((left, right)) = line.split_once(" - ") else {
+            continue;
+        };
+        if right.split_whitespace().next() == Some("cgroup2") {
+            if let Some(mount) = left.split_whitespace().nth(4) {
+                return Ok(PathBuf::from(mount));
+            }
+        }
+    }
+
+    Err(ProcError::CgroupError(
+        "No cgroup2 filesystem is mounted".to_string(),
+    ))
+}
+
+/// Resolve a cgroup path to its directory and check that it lives inside the
+/// cgroup2 mount.
+///
+/// Accepts either a full filesystem path (`/sys/fs/cgroup/user.slice`) or a
+/// path relative to the cgroup root as found in `/proc/<pid>/cgroup`
+/// (`/user.slice`).
+pub(crate) fn resolve(cgroup_path: &str) -> Result<PathBuf, ProcError> {
+    let mount = mount_point()?;
+    let requested = Path::new(cgroup_path);
+    let candidate = if requested.starts_with(&mount) {
+        requested.to_path_buf()
+    } else {
+        mount.join(cgroup_path.trim_start_matches('/'))
+    };
+
+    // Canonicalize so ".." cannot be used to escape the hierarchy
+    let dir = candidate
+        .canonicalize()
+        .map_err(|e| ProcError::CgroupError(format!("{}: {}", candidate.display(), e)))?;
+
+    if !dir.starts_with(&mount) {
+        return Err(ProcError::CgroupError(format!(
+            "{} is not under the cgroup2 mount at {}",
+            dir.display(),
+            mount.display()
+        )));
+    }
+    if !dir.join("cgroup.procs").is_file() {
+        return Err(ProcError::CgroupError(format!(
+            "{} is not a cgroup directory",
+            dir.display()
+        )));
+    }
+
+    Ok(dir)
+}
+
+/// The cgroup v2 path of `pid`, relative to the cgroup root (e.g.
+/// `/user.slice/user-1000.slice/session-2.scope`).
+pub fn cgroup_of(pid: i32) -> Result<String, ProcError> {
+    let path = format!("/proc/{}/cgroup", pid);
+    let content = fs::read_to_string(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ProcError::NotFound(pid),
+        _ => ProcError::CgroupError(format!("Failed to read {}: {}", path, e)),
+    })?;
+
+    // The unified hierarchy is the entry with hierarchy ID 0 and no controllers
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(str::to_string)
+        .ok_or_else(|| ProcError::CgroupError(format!("PID {} is not in a cgroup v2", pid)))
+}
+
+/// Current memory usage of a cgroup, if the memory controller is enabled for it.
+pub(crate) fn memory_current(dir: &Path) -> Option<u64> {
+    fs::read_to_string(dir.join("memory.current"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Write `value` to the control file `file` inside `dir`.
+pub(crate) fn write_control(dir: &Path, file: &str, value: &str) -> Result<(), ProcError> {
+    let path = dir.join(file);
+    fs::write(&path, value)
+        .map_err(|e| ProcError::CgroupError(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Whether the kernel offers `cgroup.kill` (Linux 5.14+).
+pub(crate) fn supports_kill(dir: &Path) -> bool {
+    dir.join("cgroup.kill").exists()
+}
+
+/// Freeze or thaw a cgroup and wait until `cgroup.events` confirms it. A
+/// freeze that times out is undone before the error is returned.
+pub(crate) fn set_frozen(dir: &Path, frozen: bool) -> Result<(), ProcError> {
+    write_control(dir, "cgroup.freeze", if frozen { "1" } else { "0" })?;
+
+    let start = time::Instant::now();
+    while read_event(dir, "frozen")? != Some(frozen as u64) {
+        if start.elapsed() >= FREEZE_TIMEOUT {
+            // Do not leave a freeze pending that might still complete later
+            if frozen {
+                let _ = write_control(dir, "cgroup.freeze", "0");
+            }
+            return Err(ProcError::CgroupError(format!(
+                "Timed out waiting for {} to {}",
+                dir.display(),
+                if frozen { "freeze" } else { "thaw" }
+            )));
+        }
+        thread::sleep(time::Duration::from_millis(10));
+    }
+
+    Ok(())
+}
+
+/// Whether the cgroup is currently frozen.
+pub(crate) fn is_frozen(dir: &Path) -> Result<bool, ProcError> {
+    Ok(read_event(dir, "frozen")? == Some(1))
+}
+
+/// Collect the PIDs of every process in `dir` and all of its child cgroups.
+pub(crate) fn procs_recursive(dir: &Path) -> Result<Vec<i32>, ProcError> {
+    let mut pids = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let procs_path = current.join("cgroup.procs");
+        let procs = fs::read_to_string(&procs_path).map_err(|e| {
+            ProcError::CgroupError(format!("Failed to read {}: {}", procs_path.display(), e))
+        })?;
+        pids.extend(
+            procs
+                .lines()
+                .filter_map(|line| line.trim().parse::<i32>().ok()),
+        );
+
+        let entries = fs::read_dir(&current).map_err(|e| {
+            ProcError::CgroupError(format!("Failed to list {}: {}", current.display(), e))
+        })?;
+        for entry in entries.flatten() {
+            if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                pending.push(entry.path());
+            }
+        }
+    }
+
+    Ok(pids)
+}
+
+/// Read a single key from `cgroup.events`, e.g. `populated` or `frozen`.
+fn read_event(dir: &Path, key: &str) -> Result<Option<u64>, ProcError> {
+    let path = dir.join("cgroup.events");
+    let events = fs::read_to_string(&path)
+        .map_err(|e| ProcError::CgroupError(format!("Failed to read {}: {}", path.display(), e)))?;
+
+    Ok(events.lines().find_map(|line| {
+        let (name, value) = line.split_once(' ')?;
+        (name == key).then(|| value.trim().parse().ok()).flatten()
+    }))
+}
@@ -0,0 +1,71 @@
+//! Configurable signal escalation used by the kill functions.
+
+use nix::sys::signal::Signal;
+use std::time::Duration;
+
+/// One escalation step: send `signal`, then wait up to `grace` for the process
+/// to exit before moving on to the next step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KillStep {
+    pub signal: Signal,
+    pub grace: Duration,
+}
+
+impl KillStep {
+    pub fn new(signal: Signal, grace: Duration) -> Self {
+        Self { signal, grace }
+    }
+}
+
+/// How a process is taken down: an ordered list of signals with a grace
+/// period after each, plus what to do before the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillPolicy {
+    /// Signals to send in order until the process exits.
+    pub steps: Vec<KillStep>,
+    /// Send SIGSTOP (or freeze the cgroup) before the first step, so the
+    /// target cannot do any more work while it is being killed. Without a
+    /// SIGKILL step, whatever survives is sent SIGCONT at the end.
+    pub stop_first: bool,
+    /// Send SIGCONT (or thaw the cgroup) right before every step other than
+    /// SIGKILL, so a stopped process can actually run its signal handler.
+    pub cont_before_term: bool,
+}
+
+impl KillPolicy {
+    /// SIGTERM with 30 seconds to shut down cleanly, then SIGKILL. Suited to
+    /// databases and other services that need time to flush state.
+    pub fn graceful() -> Self {
+        Self {
+            steps: vec![
+                KillStep::new(Signal::SIGTERM, Duration::from_secs(30)),
+                KillStep::new(Signal::SIGKILL, Duration::from_secs(1)),
+            ],
+            stop_first: false,
+            cont_before_term: true,
+        }
+    }
+
+    /// SIGKILL straight away, for processes that are stuck anyway.
+    pub fn immediate() -> Self {
+        Self {
+            steps: vec![KillStep::new(Signal::SIGKILL, Duration::from_secs(1))],
+            stop_first: false,
+            cont_before_term: false,
+        }
+    }
+}
+
+impl Default for KillPolicy {
+    /// SIGSTOP, then SIGTERM with 500ms to exit, then SIGKILL.
+    fn default() -> Self {
+        Self {
+            steps: vec![
+                KillStep::new(Signal::SIGTERM, Duration::from_millis(500)),
+                KillStep::new(Signal::SIGKILL, Duration::from_secs(1)),
+            ],
+            stop_first: true,
+            cont_before_term: false,
+        }
+    }
+}
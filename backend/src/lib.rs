@@ -1,12 +1,74 @@
 //! UI-agnostic process management library for Linux.
 //!
-//! Provides functions for listing processes, killing processes, killing process trees, and killing cgroups.
+//! Provides functions for listing processes, killing processes, process trees, process groups,
+//! sessions, cgroups or every process matching pkill-style criteria, pausing and resuming them,
+//! changing their CPU and I/O priority and CPU affinity, adjusting their OOM score and resource
+//! limits, finding zombies and orphaned trees, and sending arbitrary signals to processes or single
+//! threads. Every function that acts on a process refuses to touch protected processes (init,
+//! kernel threads, the caller and its ancestors, ...) unless given [`Safety::Override`]. A
+//! [`ProcessMonitor`] samples the process list, measuring CPU usage and disk I/O rates between its
+//! own refreshes and optionally PSS, USS and swap use or the threads of chosen processes, and a
+//! [`Sampler`] does so on a background thread. A [`Trash`] stops processes instead of killing them,
+//! so they can be restored or killed later.
 //! Uses `nix` and `procfs` for system interaction.
 
+mod cgroup;
+mod kill_policy;
+mod pidfd;
+mod process_diff;
+mod process_group;
+mod process_io;
 mod process_kill;
+mod process_limits;
 mod process_list;
+mod process_match;
+mod process_memory;
+mod process_monitor;
+mod process_oom;
+mod process_sched;
+mod process_signal;
+mod process_suspend;
+mod process_thread;
+mod process_zombie;
+mod safety;
+mod sampler;
+mod trash;
 mod types;
 
+pub use cgroup::cgroup_of;
+pub use kill_policy::{KillPolicy, KillStep};
+pub use process_diff::{ProcessChange, ProcessField, SnapshotDiff};
+pub use process_group::{
+    group_members, kill_group, kill_session, session_members, signal_group, signal_session,
+};
+pub use process_io::{io_stats, IoRates, IoStats};
 pub use process_kill::{kill_cgroup, kill_pid, kill_tree};
-pub use process_list::list_processes;
-pub use types::{ProcError, ProcessInfo};
+pub use process_limits::{limit, limits, set_limit, Limit, LimitUnit, Resource};
+pub use process_list::{list_processes, CpuMode};
+pub use process_match::{
+    find_matching, kill_matched, kill_matching, MatchCriteria, MatchReport, MatchedProcess,
+};
+pub use process_memory::{memory_details, MemoryDetails};
+pub use process_monitor::{ProcessMonitor, Snapshot};
+pub use process_oom::{oom_score, oom_score_adj, set_oom_score_adj, OOM_SCORE_ADJ_RANGE};
+pub use process_sched::{
+    cpu_affinity, cpu_count, io_priority, nice, sched_policy, set_cpu_affinity, set_io_priority,
+    set_nice, set_sched_policy, IoPriority, SchedPolicy, ThreadScope, IO_LEVEL_RANGE, NICE_RANGE,
+    RT_PRIORITY_RANGE,
+};
+pub use process_signal::{send_signal, send_thread_signal, SignalKind};
+pub use process_suspend::{
+    resume_cgroup, resume_pid, resume_tree, suspend_cgroup, suspend_pid, suspend_tree,
+};
+pub use process_thread::ThreadInfo;
+pub use process_zombie::{
+    analyze_zombies, kill_zombie_parent, nudge_zombie_parent, OrphanedTree, ZombieParent,
+    ZombieReport,
+};
+pub use safety::{critical_names, protection_reason, set_critical_names, ProtectionReason, Safety};
+pub use sampler::{Sample, Sampler};
+pub use trash::{Trash, TrashItem, TrashTarget};
+pub use types::{KillOutcome, PidOutcome, ProcError, ProcessInfo, Suspension, UnkillableReason};
+
+/// Re-exported so callers can build a [`KillPolicy`] without depending on `nix`.
+pub use nix::sys::signal::Signal;
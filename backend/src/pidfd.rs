@@ -0,0 +1,119 @@
+//! Process handles backed by pidfds, so signals cannot hit a recycled PID.
+
+use nix::errno::Errno;
+use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+use std::os::fd::{AsFd, AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::{ptr, thread, time};
+
+/// How often the fallback path re-checks whether a process has exited.
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(10);
+
+/// A reference to one specific process.
+///
+/// On Linux 5.3+ this holds a pidfd: signals sent through it always reach the
+/// process it was opened for, even after that process exits and its PID number
+/// is reused, and its exit can be waited for with `poll`. On older kernels the
+/// handle falls back to signalling the raw PID.
+pub(crate) struct ProcessHandle {
+    pid: i32,
+    pidfd: Option<OwnedFd>,
+}
+
+impl ProcessHandle {
+    /// Open a handle on `pid`. Fails with `ESRCH` if no such process exists.
+    pub(crate) fn open(pid: i32) -> Result<Self, Errno> {
+        // SAFETY: pidfd_open takes a PID and a flags word and returns a new fd or -1
+        let ret = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) };
+        if ret >= 0 {
+            // SAFETY: the descriptor was just created by the kernel and nothing else owns it
+            let pidfd = unsafe { OwnedFd::from_raw_fd(ret as RawFd) };
+            return Ok(Self {
+                pid,
+                pidfd: Some(pidfd),
+            });
+        }
+
+        match Errno::last() {
+            Errno::ESRCH => Err(Errno::ESRCH),
+            // ENOSYS on kernels without pidfd; fall back to the raw PID
+            _ => {
+                signal::kill(Pid::from_raw(pid), None)?;
+                Ok(Self { pid, pidfd: None })
+            }
+        }
+    }
+
+    pub(crate) fn pid(&self) -> i32 {
+        self.pid
+    }
+
+    /// Send `sig` to the process, through the pidfd when there is one.
+    pub(crate) fn signal(&self, sig: Signal) -> Result<(), Errno> {
+        self.signal_number(sig as libc::c_int)
+    }
+
+    /// Send a raw signal number, which may be a realtime signal that has no
+    /// [`Signal`] variant.
+    pub(crate) fn signal_number(&self, signo: libc::c_int) -> Result<(), Errno> {
+        let ret = match &self.pidfd {
+            // SAFETY: the pidfd is valid for the lifetime of `self`; a null
+            // siginfo makes the kernel fill it in as kill(2) would
+            Some(pidfd) => unsafe {
+                libc::syscall(
+                    libc::SYS_pidfd_send_signal,
+                    pidfd.as_raw_fd(),
+                    signo,
+                    ptr::null::<libc::siginfo_t>(),
+                    0,
+                )
+            },
+            // SAFETY: kill(2) has no memory-safety preconditions
+            None => unsafe { libc::kill(self.pid, signo) }.into(),
+        };
+        Errno::result(ret).map(drop)
+    }
+
+    /// Wait up to `timeout` for the process to exit, counting an exited but not
+    /// yet reaped zombie as exited. Returns `true` if it did.
+    pub(crate) fn wait_exit(&self, timeout: time::Duration) -> bool {
+        let deadline = time::Instant::now() + timeout;
+
+        match &self.pidfd {
+            // A pidfd becomes readable once the process has exited
+            Some(pidfd) => loop {
+                let remaining = deadline.saturating_duration_since(time::Instant::now());
+                let timeout = PollTimeout::try_from(remaining).unwrap_or(PollTimeout::MAX);
+                let mut fds = [PollFd::new(pidfd.as_fd(), PollFlags::POLLIN)];
+                match poll(&mut fds, timeout) {
+                    Ok(0) => return false,
+                    Ok(_) => return true,
+                    Err(Errno::EINTR) => continue,
+                    Err(_) => return raw_pid_exited(self.pid),
+                }
+            },
+            None => loop {
+                if raw_pid_exited(self.pid) {
+                    return true;
+                }
+                let remaining = deadline.saturating_duration_since(time::Instant::now());
+                if remaining.is_zero() {
+                    return false;
+                }
+                thread::sleep(remaining.min(POLL_INTERVAL));
+            },
+        }
+    }
+}
+
+/// Liveness check by PID number, treating zombies as exited.
+fn raw_pid_exited(pid: i32) -> bool {
+    if signal::kill(Pid::from_raw(pid), None).is_err() {
+        return true;
+    }
+    procfs::process::Process::new(pid)
+        .and_then(|proc| proc.stat())
+        .map(|stat| stat.state == 'Z')
+        .unwrap_or(true)
+}
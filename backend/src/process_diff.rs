@@ -0,0 +1,248 @@
+//! What changed between two snapshots of the process list: processes that
+//! started, processes that exited and processes whose details changed.
+
+use crate::process_monitor::Snapshot;
+use crate::types::ProcessInfo;
+use std::collections::HashMap;
+
+/// A detail of a running process that can change between snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProcessField {
+    Name,
+    Cmdline,
+    Exe,
+    Cwd,
+    State,
+    Suspended,
+    Ppid,
+    Pgid,
+    Sid,
+    /// Real or effective user.
+    User,
+    Cpu,
+    Memory,
+    /// I/O counters or rates.
+    Io,
+    Threads,
+    Nice,
+    Priority,
+    SchedPolicy,
+    CpuAffinity,
+    IoPriority,
+    OomScore,
+    OomScoreAdj,
+}
+
+/// A process found in both snapshots with some of its details changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessChange {
+    pub pid: i32,
+    /// What changed; the old and new values are in the two snapshots.
+    pub fields: Vec<ProcessField>,
+}
+
+/// Differences between two snapshots, from [`SnapshotDiff::between`].
+///
+/// A PID that was reused by a new process in between counts as one process
+/// exiting and another being added.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotDiff {
+    /// Processes that started since the old snapshot.
+    pub added: Vec<ProcessInfo>,
+    /// Processes that exited since the old snapshot, as last seen.
+    pub exited: Vec<ProcessInfo>,
+    pub changed: Vec<ProcessChange>,
+}
+
+impl SnapshotDiff {
+    /// Compare `old` with the later snapshot `new`. Each list is ordered by PID.
+    pub fn between(old: &Snapshot, new: &Snapshot) -> Self {
+        let old_by_pid: HashMap<i32, &ProcessInfo> =
+            old.processes.iter().map(|p| (p.pid, p)).collect();
+        let new_by_pid: HashMap<i32, &ProcessInfo> =
+            new.processes.iter().map(|p| (p.pid, p)).collect();
+
+        let mut diff = SnapshotDiff::default();
+        for process in &new.processes {
+            match old_by_pid.get(&process.pid) {
+                Some(before) if before.start_time == process.start_time => {
+                    let fields = changed_fields(before, process);
+                    if !fields.is_empty() {
+                        diff.changed.push(ProcessChange {
+                            pid: process.pid,
+                            fields,
+                        });
+                    }
+                }
+                _ => diff.added.push(process.clone()),
+            }
+        }
+        for process in &old.processes {
+            let same = new_by_pid
+                .get(&process.pid)
+                .is_some_and(|after| after.start_time == process.start_time);
+            if !same {
+                diff.exited.push(process.clone());
+            }
+        }
+
+        diff.added.sort_by_key(|p| p.pid);
+        diff.exited.sort_by_key(|p| p.pid);
+        diff.changed.sort_by_key(|change| change.pid);
+        diff
+    }
+
+    /// Whether the two snapshots were identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.exited.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn changed_fields(before: &ProcessInfo, after: &ProcessInfo) -> Vec<ProcessField> {
+    let checks = [
+        (ProcessField::Name, before.name != after.name),
+        (ProcessField::Cmdline, before.cmdline != after.cmdline),
+        (ProcessField::Exe, before.exe != after.exe),
+        (ProcessField::Cwd, before.cwd != after.cwd),
+        (ProcessField::State, before.state != after.state),
+        (ProcessField::Suspended, before.suspended != after.suspended),
+        (ProcessField::Ppid, before.ppid != after.ppid),
+        (ProcessField::Pgid, before.pgid != after.pgid),
+        (ProcessField::Sid, before.sid != after.sid),
+        (
+            ProcessField::User,
+            before.uid != after.uid || before.euid != after.euid,
+        ),
+        (ProcessField::Cpu, before.cpu_percent != after.cpu_percent),
+        (
+            ProcessField::Memory,
+            before.memory_bytes != after.memory_bytes
+                || before.memory_details != after.memory_details,
+        ),
+        (
+            ProcessField::Io,
+            before.io != after.io || before.io_rates != after.io_rates,
+        ),
+        (ProcessField::Threads, before.threads != after.threads),
+        (ProcessField::Nice, before.nice != after.nice),
+        (ProcessField::Priority, before.priority != after.priority),
+        (
+            ProcessField::SchedPolicy,
+            before.sched_policy != after.sched_policy,
+        ),
+        (
+            ProcessField::CpuAffinity,
+            before.cpu_affinity != after.cpu_affinity,
+        ),
+        (
+            ProcessField::IoPriority,
+            before.io_priority != after.io_priority,
+        ),
+        (ProcessField::OomScore, before.oom_score != after.oom_score),
+        (
+            ProcessField::OomScoreAdj,
+            before.oom_score_adj != after.oom_score_adj,
+        ),
+    ];
+
+    checks
+        .into_iter()
+        .filter(|&(_, changed)| changed)
+        .map(|(field, _)| field)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_sched::SchedPolicy;
+    use std::collections::HashMap;
+    use std::time::{Duration, SystemTime};
+
+    fn process(pid: i32, started: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: format!("proc{}", pid),
+            cmdline: Vec::new(),
+            exe: None,
+            cwd: None,
+            uid: 1000,
+            user: "user".to_string(),
+            euid: 1000,
+            effective_user: "user".to_string(),
+            start_time: SystemTime::UNIX_EPOCH + Duration::from_secs(started),
+            age: Duration::ZERO,
+            threads: 1,
+            cpu_percent: 0.0,
+            memory_bytes: 4096,
+            memory_details: None,
+            io: None,
+            io_rates: None,
+            state: "S".to_string(),
+            ppid: 1,
+            pgid: pid,
+            sid: pid,
+            suspended: None,
+            nice: 0,
+            priority: 20,
+            sched_policy: SchedPolicy::default(),
+            cpu_affinity: None,
+            io_priority: None,
+            oom_score: 0,
+            oom_score_adj: 0,
+        }
+    }
+
+    fn snapshot(processes: Vec<ProcessInfo>) -> Snapshot {
+        Snapshot {
+            processes,
+            threads: HashMap::new(),
+            taken_at: SystemTime::now(),
+        }
+    }
+
+    fn pids(processes: &[ProcessInfo]) -> Vec<i32> {
+        processes.iter().map(|p| p.pid).collect()
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_differences() {
+        let old = snapshot(vec![process(1, 0), process(2, 10)]);
+        assert!(SnapshotDiff::between(&old, &old.clone()).is_empty());
+    }
+
+    #[test]
+    fn added_exited_and_changed_processes() {
+        let old = snapshot(vec![process(1, 0), process(5, 10), process(7, 20)]);
+        let mut busy = process(5, 10);
+        busy.cpu_percent = 50.0;
+        busy.state = "R".to_string();
+        let new = snapshot(vec![process(9, 30), process(1, 0), busy]);
+
+        let diff = SnapshotDiff::between(&old, &new);
+        assert_eq!(pids(&diff.added), vec![9]);
+        assert_eq!(pids(&diff.exited), vec![7]);
+        assert_eq!(
+            diff.changed,
+            vec![ProcessChange {
+                pid: 5,
+                fields: vec![ProcessField::State, ProcessField::Cpu],
+            }]
+        );
+    }
+
+    #[test]
+    fn a_reused_pid_is_an_exit_and_an_addition() {
+        let old = snapshot(vec![process(1, 0), process(42, 10)]);
+        let mut reused = process(42, 25);
+        reused.name = "other".to_string();
+        let new = snapshot(vec![process(1, 0), reused]);
+
+        let diff = SnapshotDiff::between(&old, &new);
+        assert_eq!(pids(&diff.added), vec![42]);
+        assert_eq!(diff.added[0].name, "other");
+        assert_eq!(pids(&diff.exited), vec![42]);
+        assert_eq!(diff.exited[0].name, "proc42");
+        assert!(diff.changed.is_empty());
+    }
+}
@@ -0,0 +1,211 @@
+//! Signalling and killing a whole process group or session at once, e.g. a
+//! shell pipeline or everything started from a terminal.
+
+use crate::kill_policy::KillPolicy;
+use crate::pidfd::ProcessHandle;
+use crate::process_kill::escalate;
+use crate::process_signal::SignalKind;
+use crate::safety::{self, Safety};
+use crate::types::{PidOutcome, ProcError};
+use nix::errno::Errno;
+use nix::sys::signal::Signal;
+use std::collections::HashSet;
+
+/// Which processes belong together.
+#[derive(Clone, Copy)]
+enum Membership {
+    Group(i32),
+    Session(i32),
+}
+
+impl Membership {
+    fn id(self) -> i32 {
+        match self {
+            Membership::Group(id) | Membership::Session(id) => id,
+        }
+    }
+
+    fn contains(self, stat: &procfs::process::Stat) -> bool {
+        match self {
+            Membership::Group(pgid) => stat.pgrp == pgid,
+            Membership::Session(sid) => stat.session == sid,
+        }
+    }
+}
+
+/// PIDs of every process in the process group `pgid`.
+pub fn group_members(pgid: i32) -> Result<Vec<i32>, ProcError> {
+    members(Membership::Group(pgid))
+}
+
+/// PIDs of every process in the session `sid`.
+pub fn session_members(sid: i32) -> Result<Vec<i32>, ProcError> {
+    members(Membership::Session(sid))
+}
+
+/// Send a single signal to every process in a process group with `killpg`,
+/// like the shell does for job control. Returns the PIDs that were in the group.
+///
+/// Unless `safety` overrides it, nothing is sent if any member is protected.
+pub fn signal_group(
+    pgid: i32,
+    signal: impl Into<SignalKind>,
+    safety: Safety,
+) -> Result<Vec<i32>, ProcError> {
+    let members = checked_members(Membership::Group(pgid), safety)?;
+    let signal = signal.into();
+    let number = signal
+        .number()
+        .ok_or_else(|| ProcError::SignalError(pgid, format!("{} is out of range", signal)))?;
+
+    killpg(pgid, number).map_err(|e| ProcError::from_errno(pgid, e))?;
+    Ok(members)
+}
+
+/// Send a single signal to every process in a session. There is no syscall
+/// for this, so each member is signalled through its own pidfd. Returns the
+/// PIDs that were signalled.
+///
+/// Unless `safety` overrides it, nothing is sent if any member is protected.
+pub fn signal_session(
+    sid: i32,
+    signal: impl Into<SignalKind>,
+    safety: Safety,
+) -> Result<Vec<i32>, ProcError> {
+    let membership = Membership::Session(sid);
+    checked_members(membership, safety)?;
+    let signal = signal.into();
+    let number = signal
+        .number()
+        .ok_or_else(|| ProcError::SignalError(sid, format!("{} is out of range", signal)))?;
+
+    let mut signalled = Vec::new();
+    for handle in open_members(membership, &HashSet::new())? {
+        match handle.signal_number(number) {
+            Ok(()) => signalled.push(handle.pid()),
+            // Exited in the meantime
+            Err(Errno::ESRCH) => {}
+            Err(e) => return Err(ProcError::from_errno(handle.pid(), e)),
+        }
+    }
+    Ok(signalled)
+}
+
+/// Kill every process in a process group, escalating through the signals of
+/// `policy`.
+///
+/// Each signal is delivered with `killpg`, so a member forked while the group
+/// is being killed gets it too. Returns one [`PidOutcome`] per process that was
+/// in the group when the kill started.
+///
+/// Unless `safety` overrides it, nothing is touched if any member is protected.
+pub fn kill_group(
+    pgid: i32,
+    policy: &KillPolicy,
+    safety: Safety,
+) -> Result<Vec<PidOutcome>, ProcError> {
+    checked_members(Membership::Group(pgid), safety)?;
+    let handles = open_members(Membership::Group(pgid), &HashSet::new())?;
+
+    if policy.stop_first {
+        let _ = killpg(pgid, Signal::SIGSTOP as i32);
+    }
+
+    Ok(escalate(handles, policy, |step| {
+        if policy.cont_before_term && step.signal != Signal::SIGKILL {
+            let _ = killpg(pgid, Signal::SIGCONT as i32);
+        }
+        match killpg(pgid, step.signal as i32) {
+            // Every member exited since the last step
+            Ok(()) | Err(Errno::ESRCH) => Ok(true),
+            Err(e) => Err(ProcError::from_errno(pgid, e)),
+        }
+    }))
+}
+
+/// Kill every process in a session, escalating through the signals of
+/// `policy`.
+///
+/// The whole session is always stopped with SIGSTOP first, rescanning until no
+/// member is left running, so nothing can fork new members out of reach.
+/// Returns one [`PidOutcome`] per process in the session.
+///
+/// Unless `safety` overrides it, nothing is touched if any member is protected.
+pub fn kill_session(
+    sid: i32,
+    policy: &KillPolicy,
+    safety: Safety,
+) -> Result<Vec<PidOutcome>, ProcError> {
+    let membership = Membership::Session(sid);
+    checked_members(membership, safety)?;
+
+    let mut stopped = HashSet::new();
+    let mut handles = Vec::new();
+    loop {
+        let new = open_members(membership, &stopped)?;
+        if new.is_empty() {
+            break;
+        }
+        for handle in new {
+            let _ = handle.signal(Signal::SIGSTOP);
+            stopped.insert(handle.pid());
+            handles.push(handle);
+        }
+    }
+
+    Ok(escalate(handles, policy, |_| Ok(false)))
+}
+
+fn members(membership: Membership) -> Result<Vec<i32>, ProcError> {
+    let all_procs = procfs::process::all_processes()
+        .map_err(|e| ProcError::Other(format!("Failed to read /proc: {}", e)))?;
+
+    Ok(all_procs
+        .flatten()
+        .filter_map(|proc| proc.stat().ok())
+        .filter(|stat| membership.contains(stat))
+        .map(|stat| stat.pid)
+        .collect())
+}
+
+/// List the members, failing if there are none or if any is protected.
+fn checked_members(membership: Membership, safety: Safety) -> Result<Vec<i32>, ProcError> {
+    let members = members(membership)?;
+    if members.is_empty() {
+        return Err(ProcError::NotFound(membership.id()));
+    }
+    for &pid in &members {
+        safety::check(pid, safety)?;
+    }
+    Ok(members)
+}
+
+/// Open handles on the current members not in `skip`.
+fn open_members(
+    membership: Membership,
+    skip: &HashSet<i32>,
+) -> Result<Vec<ProcessHandle>, ProcError> {
+    let mut handles = Vec::new();
+    for pid in members(membership)? {
+        if skip.contains(&pid) {
+            continue;
+        }
+        let Ok(handle) = ProcessHandle::open(pid) else {
+            continue;
+        };
+        // Once the handle is open the PID cannot be reused, so checking
+        // membership again afterwards is enough
+        let still_member = procfs::process::Process::new(pid)
+            .and_then(|proc| proc.stat())
+            .is_ok_and(|stat| membership.contains(&stat));
+        if still_member {
+            handles.push(handle);
+        }
+    }
+    Ok(handles)
+}
+
+fn killpg(pgid: i32, signal: libc::c_int) -> Result<(), Errno> {
+    // SAFETY: killpg(3) has no memory-safety preconditions
+    Errno::result(unsafe { libc::killpg(pgid, signal) }).map(drop)
+}
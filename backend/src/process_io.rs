@@ -0,0 +1,268 @@
+//! Disk I/O statistics from `/proc/<pid>/io`, with per-second rates measured
+//! between listings.
+
+use crate::process_list::{read_uptime, retain_current};
+use crate::types::ProcError;
+use std::collections::HashMap;
+
+/// I/O done by a process since it started, including that of its exited
+/// threads and of the children it has reaped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IoStats {
+    /// Bytes fetched from storage; reads served from the page cache are not
+    /// counted.
+    pub read_bytes: u64,
+    /// Bytes sent, or to be sent, to storage.
+    pub write_bytes: u64,
+    /// Read system calls (`read`, `pread`, ...), whatever they read from.
+    pub read_syscalls: u64,
+    /// Write system calls (`write`, `pwrite`, ...), whatever they wrote to.
+    pub write_syscalls: u64,
+    /// Bytes counted in `write_bytes` that were never written after all,
+    /// e.g. because the file was truncated or deleted while still dirty.
+    pub cancelled_write_bytes: u64,
+}
+
+impl From<procfs::process::Io> for IoStats {
+    fn from(io: procfs::process::Io) -> Self {
+        Self {
+            read_bytes: io.read_bytes,
+            write_bytes: io.write_bytes,
+            read_syscalls: io.syscr,
+            write_syscalls: io.syscw,
+            cancelled_write_bytes: io.cancelled_write_bytes,
+        }
+    }
+}
+
+/// [`IoStats`] per second, averaged since the previous listing.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IoRates {
+    pub read_bytes: f64,
+    pub write_bytes: f64,
+    pub read_syscalls: f64,
+    pub write_syscalls: f64,
+    pub cancelled_write_bytes: f64,
+}
+
+/// Read the I/O done by `pid` so far.
+///
+/// Like reading its memory, this needs the same permission as attaching a
+/// debugger, so it usually fails for other users' processes.
+pub fn io_stats(pid: i32) -> Result<IoStats, ProcError> {
+    procfs::process::Process::new(pid)
+        .and_then(|proc| proc.io())
+        .map(IoStats::from)
+        .map_err(|e| match e {
+            procfs::ProcError::NotFound(_) => ProcError::NotFound(pid),
+            procfs::ProcError::PermissionDenied(_) => ProcError::PermissionDenied(pid),
+            e => e.into(),
+        })
+}
+
+/// I/O counters of a process as of the last listing.
+struct ProcessIoData {
+    /// Start time in clock ticks after boot, to notice a reused PID.
+    start_time: u64,
+    stats: IoStats,
+    rates: IoRates,
+}
+
+/// I/O counters seen at the previous listing, to measure rates against.
+pub(crate) struct IoTracker {
+    process_data: HashMap<i32, ProcessIoData>,
+    /// Time since boot of the listing the rates are measured from, in
+    /// seconds, `None` on the first listing.
+    previous: Option<f64>,
+    current: Option<f64>,
+    /// Whether time passed since the last listing; if not, the previous
+    /// rates still stand.
+    advanced: bool,
+    clock_ticks_per_second: u64,
+}
+
+impl IoTracker {
+    pub(crate) fn new() -> Self {
+        Self {
+            process_data: HashMap::new(),
+            previous: None,
+            current: None,
+            advanced: false,
+            clock_ticks_per_second: procfs::ticks_per_second(),
+        }
+    }
+
+    /// Note the time of a new listing.
+    pub(crate) fn begin_listing(&mut self) {
+        self.begin_listing_at(read_uptime());
+    }
+
+    fn begin_listing_at(&mut self, uptime: Option<f64>) {
+        self.advanced = false;
+        let Some(uptime) = uptime else {
+            return;
+        };
+        if self.current.is_some_and(|current| uptime <= current) {
+            return;
+        }
+        self.previous = self.current.replace(uptime);
+        self.advanced = true;
+    }
+
+    /// I/O rates of a process since the previous listing, given its start
+    /// time in clock ticks and its counters now.
+    pub(crate) fn calculate_rates(&mut self, pid: i32, start_time: u64, stats: IoStats) -> IoRates {
+        let (Some(previous), Some(current)) = (self.previous, self.current) else {
+            self.remember(pid, start_time, stats, IoRates::default());
+            return IoRates::default();
+        };
+
+        let known = self
+            .process_data
+            .get(&pid)
+            .filter(|data| data.start_time == start_time);
+        let before = match known {
+            Some(data) if !self.advanced => return data.rates,
+            Some(data) => Some(data.stats),
+            // Started since the previous listing, so all its I/O is new
+            None if start_time as f64 / self.clock_ticks_per_second as f64 >= previous => {
+                Some(IoStats::default())
+            }
+            None => None,
+        };
+
+        let rates = match before {
+            Some(before) => {
+                let elapsed = current - previous;
+                let rate = |now: u64, then: u64| now.saturating_sub(then) as f64 / elapsed;
+                IoRates {
+                    read_bytes: rate(stats.read_bytes, before.read_bytes),
+                    write_bytes: rate(stats.write_bytes, before.write_bytes),
+                    read_syscalls: rate(stats.read_syscalls, before.read_syscalls),
+                    write_syscalls: rate(stats.write_syscalls, before.write_syscalls),
+                    cancelled_write_bytes: rate(
+                        stats.cancelled_write_bytes,
+                        before.cancelled_write_bytes,
+                    ),
+                }
+            }
+            None => IoRates::default(),
+        };
+
+        self.remember(pid, start_time, stats, rates);
+        rates
+    }
+
+    fn remember(&mut self, pid: i32, start_time: u64, stats: IoStats, rates: IoRates) {
+        self.process_data.insert(
+            pid,
+            ProcessIoData {
+                start_time,
+                stats,
+                rates,
+            },
+        );
+    }
+
+    pub(crate) fn cleanup_old_processes(&mut self, current_pids: &[i32]) {
+        retain_current(&mut self.process_data, current_pids);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tracker counting 100 clock ticks per second, like most kernels.
+    fn tracker() -> IoTracker {
+        IoTracker {
+            clock_ticks_per_second: 100,
+            ..IoTracker::new()
+        }
+    }
+
+    fn read(bytes: u64) -> IoStats {
+        IoStats {
+            read_bytes: bytes,
+            read_syscalls: bytes / 100,
+            ..IoStats::default()
+        }
+    }
+
+    #[test]
+    fn rates_are_zero_on_the_first_listing() {
+        let mut tracker = tracker();
+        tracker.begin_listing_at(Some(100.0));
+        assert_eq!(
+            tracker.calculate_rates(10, 500, read(1000)),
+            IoRates::default()
+        );
+    }
+
+    #[test]
+    fn rates_are_per_second_since_the_previous_listing() {
+        let mut tracker = tracker();
+        tracker.begin_listing_at(Some(100.0));
+        tracker.calculate_rates(10, 500, read(1000));
+
+        tracker.begin_listing_at(Some(102.0));
+        let rates = tracker.calculate_rates(10, 500, read(5000));
+        assert_eq!(rates.read_bytes, 2000.0);
+        assert_eq!(rates.read_syscalls, 20.0);
+        assert_eq!(rates.write_bytes, 0.0);
+
+        // No time passed, so the rates still stand
+        tracker.begin_listing_at(Some(102.0));
+        assert_eq!(tracker.calculate_rates(10, 500, read(9000)), rates);
+    }
+
+    #[test]
+    fn a_process_started_since_the_previous_listing_counts_from_zero() {
+        let mut tracker = tracker();
+        tracker.begin_listing_at(Some(100.0));
+
+        // Started at 101 s, after the previous listing
+        tracker.begin_listing_at(Some(102.0));
+        assert_eq!(
+            tracker.calculate_rates(20, 10100, read(4000)).read_bytes,
+            2000.0
+        );
+
+        // Running since 50 s but not seen before: no baseline to measure from
+        assert_eq!(
+            tracker.calculate_rates(21, 5000, read(4000)).read_bytes,
+            0.0
+        );
+    }
+
+    #[test]
+    fn a_reused_pid_is_measured_as_a_new_process() {
+        let mut tracker = tracker();
+        tracker.begin_listing_at(Some(100.0));
+        tracker.calculate_rates(10, 500, read(1_000_000));
+
+        // PID 10 now belongs to a process started at 101 s, whose counters
+        // have nothing to do with the old ones
+        tracker.begin_listing_at(Some(102.0));
+        assert_eq!(
+            tracker.calculate_rates(10, 10100, read(4000)).read_bytes,
+            2000.0
+        );
+    }
+
+    #[test]
+    fn cleanup_forgets_exited_processes() {
+        let mut tracker = tracker();
+        tracker.begin_listing_at(Some(100.0));
+        tracker.calculate_rates(10, 500, read(1000));
+        tracker.calculate_rates(11, 500, read(1000));
+        tracker.cleanup_old_processes(&[11]);
+
+        tracker.begin_listing_at(Some(102.0));
+        assert_eq!(tracker.calculate_rates(10, 500, read(5000)).read_bytes, 0.0);
+        assert_eq!(
+            tracker.calculate_rates(11, 500, read(5000)).read_bytes,
+            2000.0
+        );
+    }
+}
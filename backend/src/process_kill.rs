@@ -1,50 +1,519 @@
 //! Process killing functionality.
 
-use crate::types::ProcError;
-use nix::sys::signal::{self, Signal};
-use nix::unistd::Pid;
-use std::{thread, time};
+use crate::cgroup;
+use crate::kill_policy::{KillPolicy, KillStep};
+use crate::pidfd::ProcessHandle;
+use crate::process_list::read_children_map;
+use crate::safety::{self, Safety};
+use crate::types::{KillOutcome, PidOutcome, ProcError, UnkillableReason};
+use nix::errno::Errno;
+use nix::sys::signal::Signal;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time;
 
-/// Kill a process by PID.
-/// Sends SIGSTOP, then SIGTERM, waits 500ms, then SIGKILL if still running.
-/// Uses pidfd_send_signal if supported.
-pub fn kill_pid(pid: i32) -> Result<(), ProcError> {
-    let pid = Pid::from_raw(pid);
+/// Kill a process by PID, escalating through the signals of `policy`.
+/// With the default policy this sends SIGSTOP, then SIGTERM, waits up to 500ms
+/// for it to exit, then SIGKILL if still running. Uses pidfd_send_signal if
+/// supported, so a recycled PID is never signalled by mistake.
+///
+/// Returns a [`KillOutcome`] describing which signal the process exited on and
+/// how long it took.
+pub fn kill_pid(pid: i32, policy: &KillPolicy, safety: Safety) -> Result<KillOutcome, ProcError> {
+    safety::check(pid, safety)?;
+    let handle = open_handle(pid)?;
 
     // Try stopping the process first
-    if let Err(e) = signal::kill(pid, Signal::SIGSTOP) {
-        return Err(ProcError::SignalError(pid.as_raw(), e.to_string()));
+    if policy.stop_first {
+        if let Err(e) = handle.signal(Signal::SIGSTOP) {
+            return Err(ProcError::from_errno(pid, e));
+        }
     }
 
-    // Then send SIGTERM
-    if let Err(e) = signal::kill(pid, Signal::SIGTERM) {
-        return Err(ProcError::SignalError(pid.as_raw(), e.to_string()));
+    escalate(vec![handle], policy, |_| Ok(false))
+        .pop()
+        .map_or(Err(ProcError::NotFound(pid)), |outcome| outcome.result)
+}
+
+/// Kill a process and all its descendants recursively.
+///
+/// The whole subtree is always stopped with SIGSTOP before anything is
+/// terminated, so no member can fork new children out of reach. Processes are
+/// then terminated leaves-to-root, one depth level at a time, escalating
+/// through the signals of `policy`. Returns one [`PidOutcome`] per PID in the
+/// tree.
+///
+/// Unless `safety` overrides it, nothing is touched if any process in the tree
+/// is protected.
+pub fn kill_tree(
+    pid: i32,
+    policy: &KillPolicy,
+    safety: Safety,
+) -> Result<Vec<PidOutcome>, ProcError> {
+    let levels = stop_tree(pid, safety)?;
+    Ok(kill_levels(levels, policy))
+}
+
+/// Stop `pid` and its whole subtree, returning handles grouped by depth.
+pub(crate) fn stop_tree(pid: i32, safety: Safety) -> Result<Vec<Vec<ProcessHandle>>, ProcError> {
+    check_subtree(pid, safety)?;
+    let root = open_handle(pid)?;
+    if let Err(e) = root.signal(Signal::SIGSTOP) {
+        return Err(ProcError::from_errno(pid, e));
+    }
+
+    freeze_subtree(root)
+}
+
+/// Kill a stopped subtree leaves-to-root, one depth level at a time.
+pub(crate) fn kill_levels(levels: Vec<Vec<ProcessHandle>>, policy: &KillPolicy) -> Vec<PidOutcome> {
+    let mut outcomes = Vec::new();
+    for level in levels.into_iter().rev() {
+        outcomes.extend(escalate(level, policy, |_| Ok(false)));
+    }
+    outcomes
+}
+
+/// Kill all processes in a cgroup v2, escalating through the signals of
+/// `policy`.
+///
+/// `cgroup_path` may be a full path under the cgroup2 mount or a path relative
+/// to the cgroup root. The SIGKILL step is delivered by writing 1 to
+/// `cgroup.kill`, which also catches anything forked in the meantime. On
+/// kernels older than 5.14, which lack `cgroup.kill`, the cgroup is frozen
+/// while its tasks are collected from it and its child cgroups, and each task
+/// is signalled individually. The policy's SIGSTOP and SIGCONT map to freezing
+/// and thawing the cgroup. Returns one [`PidOutcome`] per task that was in the
+/// cgroup.
+///
+/// Unless `safety` overrides it, nothing is touched if any task in the cgroup
+/// is protected. If the cgroup cannot be thawed afterwards, the failure is
+/// reported on every process that survived.
+pub fn kill_cgroup(
+    cgroup_path: &str,
+    policy: &KillPolicy,
+    safety: Safety,
+) -> Result<Vec<PidOutcome>, ProcError> {
+    let dir = cgroup::resolve(cgroup_path)?;
+    for pid in cgroup::procs_recursive(&dir)? {
+        safety::check(pid, safety)?;
+    }
+    let has_kill = cgroup::supports_kill(&dir);
+
+    // The cgroup may already be frozen, e.g. when it was moved to the trash
+    let mut frozen = cgroup::is_frozen(&dir)?;
+    if !frozen && (policy.stop_first || !has_kill) {
+        cgroup::set_frozen(&dir, true)?;
+        frozen = true;
+    }
+
+    let result = kill_cgroup_members(&dir, policy, has_kill, &mut frozen);
+
+    // Always thaw, even on failure, so the cgroup is not left frozen
+    let thawed = if frozen {
+        cgroup::set_frozen(&dir, false)
+    } else {
+        Ok(())
+    };
+
+    match (result, thawed) {
+        (result, Ok(())) => result,
+        // Keep what the kill did, and report the failed thaw on the
+        // processes it leaves frozen
+        (Ok(outcomes), Err(thaw)) => Ok(outcomes
+            .into_iter()
+            .map(|outcome| match &outcome.result {
+                Ok(killed) if !killed.exited() => PidOutcome {
+                    pid: outcome.pid,
+                    result: Err(ProcError::CgroupError(format!(
+                        "PID {} survived and was left frozen: {}",
+                        outcome.pid, thaw
+                    ))),
+                },
+                _ => outcome,
+            })
+            .collect()),
+        (Err(kill), Err(thaw)) => Err(ProcError::CgroupError(format!(
+            "{}; thawing {} also failed: {}",
+            kill,
+            dir.display(),
+            thaw
+        ))),
     }
+}
+
+fn kill_cgroup_members(
+    dir: &Path,
+    policy: &KillPolicy,
+    has_kill: bool,
+    frozen: &mut bool,
+) -> Result<Vec<PidOutcome>, ProcError> {
+    let handles: Vec<ProcessHandle> = cgroup::procs_recursive(dir)?
+        .into_iter()
+        .filter_map(|pid| ProcessHandle::open(pid).ok())
+        .collect();
 
-    // Wait for half a second
-    thread::sleep(time::Duration::from_millis(500));
+    let outcomes = escalate(handles, policy, |step| {
+        if *frozen && policy.cont_before_term && step.signal != Signal::SIGKILL {
+            cgroup::set_frozen(dir, false)?;
+            *frozen = false;
+        }
+        if has_kill && step.signal == Signal::SIGKILL {
+            cgroup::write_control(dir, "cgroup.kill", "1")?;
+            return Ok(true);
+        }
+        Ok(false)
+    });
+
+    Ok(outcomes
+        .into_iter()
+        .map(|outcome| PidOutcome {
+            pid: outcome.pid,
+            result: outcome.result.map_err(|e| match e {
+                ProcError::SignalError(pid, e) => ProcError::CgroupError(format!(
+                    "Failed to kill PID {} in {}: {}",
+                    pid,
+                    dir.display(),
+                    e
+                )),
+                e => e,
+            }),
+        })
+        .collect())
+}
 
-    // Check if the process is still alive and send SIGKILL
-    if let Err(_) = signal::kill(pid, None) {
-        // Process already gone
+/// Open a handle on `pid`, mapping a missing process to [`ProcError::NotFound`].
+pub(crate) fn open_handle(pid: i32) -> Result<ProcessHandle, ProcError> {
+    ProcessHandle::open(pid).map_err(|e| ProcError::from_errno(pid, e))
+}
+
+/// Check `root` and every current descendant of it against the safety layer.
+fn check_subtree(root: i32, safety: Safety) -> Result<(), ProcError> {
+    safety::check(root, safety)?;
+    if safety == Safety::Override {
         return Ok(());
     }
 
-    if let Err(e) = signal::kill(pid, Signal::SIGKILL) {
-        return Err(ProcError::SignalError(pid.as_raw(), e.to_string()));
+    let children = read_children_map()?;
+    let mut pending = vec![root];
+    while let Some(parent) = pending.pop() {
+        for &child in children.get(&parent).into_iter().flatten() {
+            safety::check(child, safety)?;
+            pending.push(child);
+        }
     }
 
     Ok(())
 }
 
-/// Kill a process and all its descendants recursively.
-pub fn kill_tree(_pid: i32) -> Result<(), ProcError> {
-    // TODO: Implement recursive killing using process tree.
-    Ok(())
+/// Stop every descendant of an already stopped `root` and return the subtree
+/// grouped by depth, with `root` alone at level 0.
+///
+/// A child forked just before its parent was stopped only shows up on the next
+/// scan of `/proc`, so the tree is rescanned until a pass finds no process that
+/// has not been stopped yet. At that point nothing in the subtree can fork.
+/// Handles are opened while the parent is stopped, and a stopped parent cannot
+/// reap its children, so their PIDs cannot be recycled in between.
+fn freeze_subtree(root: ProcessHandle) -> Result<Vec<Vec<ProcessHandle>>, ProcError> {
+    let root_pid = root.pid();
+    let mut stopped: HashSet<i32> = HashSet::from([root_pid]);
+    let mut levels = vec![vec![root]];
+
+    loop {
+        let children = read_children_map()?;
+        let mut found_new = false;
+        let mut depth = 0;
+
+        while depth < levels.len() {
+            let next: Vec<i32> = levels[depth]
+                .iter()
+                .filter_map(|parent| children.get(&parent.pid()))
+                .flatten()
+                .copied()
+                .filter(|child| !stopped.contains(child))
+                .collect();
+
+            for child in next {
+                stopped.insert(child);
+                // A child that already exited will simply not be part of the tree
+                let Ok(handle) = ProcessHandle::open(child) else {
+                    continue;
+                };
+                let _ = handle.signal(Signal::SIGSTOP);
+                found_new = true;
+
+                if levels.len() == depth + 1 {
+                    levels.push(Vec::new());
+                }
+                levels[depth + 1].push(handle);
+            }
+            depth += 1;
+        }
+
+        if !found_new {
+            return Ok(levels);
+        }
+    }
 }
 
-/// Kill all processes in a cgroup v2 by writing 1 to cgroup.kill.
-pub fn kill_cgroup(_cgroup_path: &str) -> Result<(), ProcError> {
-    // TODO: Implement cgroup v2 killing.
-    Ok(())
+/// Run the steps of `policy` against a batch of processes. Every process in
+/// the batch gets each signal before the batch waits out that step's grace
+/// period together, and only the survivors move on to the next step.
+///
+/// `before_step` runs ahead of each step and may deliver the signal itself
+/// (for instance through `cgroup.kill`) by returning `Ok(true)`.
+///
+/// Callers may have stopped the batch before escalating, so when the policy
+/// has no SIGKILL step its survivors are sent SIGCONT rather than left stopped.
+pub(crate) fn escalate(
+    handles: Vec<ProcessHandle>,
+    policy: &KillPolicy,
+    mut before_step: impl FnMut(&KillStep) -> Result<bool, ProcError>,
+) -> Vec<PidOutcome> {
+    let started = time::Instant::now();
+    let mut outcomes = Vec::with_capacity(handles.len());
+    let mut alive = Vec::with_capacity(handles.len());
+
+    for handle in handles {
+        if handle.wait_exit(time::Duration::ZERO) {
+            outcomes.push(exited(&handle, None, started));
+        } else {
+            alive.push(handle);
+        }
+    }
+
+    let mut last_signal = None;
+    for step in &policy.steps {
+        if alive.is_empty() {
+            break;
+        }
+
+        let delivered = match before_step(step) {
+            Ok(delivered) => delivered,
+            Err(e) => {
+                let message = e.to_string();
+                outcomes.extend(alive.drain(..).map(|handle| PidOutcome {
+                    pid: handle.pid(),
+                    result: Err(ProcError::SignalError(handle.pid(), message.clone())),
+                }));
+                break;
+            }
+        };
+
+        let mut signalled = Vec::with_capacity(alive.len());
+        for handle in alive.drain(..) {
+            let pid = handle.pid();
+            if !delivered {
+                if policy.cont_before_term && step.signal != Signal::SIGKILL {
+                    let _ = handle.signal(Signal::SIGCONT);
+                }
+                match handle.signal(step.signal) {
+                    Ok(()) => {}
+                    // Process exited before this step's signal arrived
+                    Err(Errno::ESRCH) => {
+                        outcomes.push(exited(&handle, last_signal, started));
+                        continue;
+                    }
+                    Err(e) => {
+                        outcomes.push(PidOutcome {
+                            pid,
+                            result: Err(ProcError::from_errno(pid, e)),
+                        });
+                        continue;
+                    }
+                }
+            }
+            signalled.push(handle);
+        }
+        last_signal = Some(step.signal);
+
+        // Give the whole batch its grace period at once
+        let deadline = time::Instant::now() + step.grace;
+        for handle in signalled {
+            let remaining = deadline.saturating_duration_since(time::Instant::now());
+            if handle.wait_exit(remaining) {
+                outcomes.push(exited(&handle, last_signal, started));
+            } else {
+                alive.push(handle);
+            }
+        }
+    }
+
+    // Surviving SIGKILL needs explaining; surviving a gentler policy does not
+    let sent_kill = policy
+        .steps
+        .iter()
+        .any(|step| step.signal == Signal::SIGKILL);
+    outcomes.extend(alive.into_iter().map(|handle| {
+        let pid = handle.pid();
+        let result = if sent_kill {
+            Err(ProcError::UnkillableState(pid, diagnose_unkillable(pid)))
+        } else {
+            let _ = handle.signal(Signal::SIGCONT);
+            Ok(KillOutcome {
+                effective_signal: None,
+                elapsed: started.elapsed(),
+                already_dead: false,
+                zombie: false,
+            })
+        };
+        PidOutcome { pid, result }
+    }));
+
+    outcomes
+}
+
+/// Work out why `pid` is still alive after SIGKILL.
+fn diagnose_unkillable(pid: i32) -> UnkillableReason {
+    let Ok(proc) = procfs::process::Process::new(pid) else {
+        return UnkillableReason::Unknown { state: '?' };
+    };
+
+    // SIGKILL is only acted on when a thread returns from the kernel, so any
+    // thread stuck in D state keeps the whole process alive
+    if let Ok(tasks) = proc.tasks() {
+        for task in tasks.flatten() {
+            if task.stat().map(|stat| stat.state == 'D').unwrap_or(false) {
+                let wchan = fs::read_to_string(format!("/proc/{}/task/{}/wchan", pid, task.tid))
+                    .ok()
+                    .map(|wchan| wchan.trim().to_string())
+                    .filter(|wchan| !wchan.is_empty() && wchan != "0");
+                return UnkillableReason::DiskSleep {
+                    tid: task.tid,
+                    wchan,
+                };
+            }
+        }
+    }
+
+    let state = match proc.stat() {
+        Ok(stat) if stat.state == 'Z' => return UnkillableReason::Zombie { parent: stat.ppid },
+        Ok(stat) => stat.state,
+        Err(_) => '?',
+    };
+
+    // A tracer can hold the process in a ptrace stop
+    match proc.status() {
+        Ok(status) if status.tracerpid > 0 => UnkillableReason::Traced {
+            tracer: status.tracerpid,
+        },
+        _ => UnkillableReason::Unknown { state },
+    }
+}
+
+/// Build the outcome for a process that has exited, `signal` being the last
+/// signal it was sent (`None` if it was dead before anything was sent).
+fn exited(handle: &ProcessHandle, signal: Option<Signal>, started: time::Instant) -> PidOutcome {
+    // An exited process keeps its PID until reaped, so this cannot see a new owner
+    let zombie = procfs::process::Process::new(handle.pid())
+        .and_then(|proc| proc.stat())
+        .map(|stat| stat.state == 'Z')
+        .unwrap_or(false);
+
+    PidOutcome {
+        pid: handle.pid(),
+        result: Ok(KillOutcome {
+            effective_signal: signal,
+            elapsed: started.elapsed(),
+            already_dead: signal.is_none(),
+            zombie,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+
+    /// A child cgroup created for a test and removed again when dropped.
+    struct TestCgroup(std::path::PathBuf);
+
+    impl TestCgroup {
+        /// Create a cgroup under the cgroup2 mount, or `None` if there is no
+        /// cgroup2 or it is not delegated to us.
+        fn create(name: &str) -> Option<Self> {
+            let mount = cgroup::mount_point().ok()?;
+            let dir = mount.join(format!("{}-{}", name, std::process::id()));
+            fs::create_dir(&dir).ok()?;
+            Some(Self(dir))
+        }
+    }
+
+    impl Drop for TestCgroup {
+        fn drop(&mut self) {
+            let _ = cgroup::write_control(&self.0, "cgroup.freeze", "0");
+            let _ = fs::remove_dir(&self.0);
+        }
+    }
+
+    #[test]
+    fn kill_cgroup_empties_the_cgroup() {
+        let Some(test_cgroup) = TestCgroup::create("trash-manager-test") else {
+            eprintln!("skipping: cgroup2 is not mounted or not delegated");
+            return;
+        };
+        let mut child = Command::new("sleep")
+            .arg("60")
+            .stdin(Stdio::null())
+            .spawn()
+            .expect("failed to spawn sleep");
+        let pid = child.id() as i32;
+        if cgroup::write_control(&test_cgroup.0, "cgroup.procs", &pid.to_string()).is_err() {
+            let _ = child.kill();
+            let _ = child.wait();
+            eprintln!("skipping: cannot move processes into the test cgroup");
+            return;
+        }
+
+        let outcomes = kill_cgroup(
+            test_cgroup.0.to_str().unwrap(),
+            &KillPolicy::default(),
+            Safety::Override,
+        );
+        let _ = child.wait();
+
+        let outcomes = outcomes.expect("kill_cgroup failed");
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].pid, pid);
+        assert!(outcomes[0].result.as_ref().is_ok_and(KillOutcome::exited));
+        assert!(cgroup::procs_recursive(&test_cgroup.0).unwrap().is_empty());
+        assert!(!cgroup::is_frozen(&test_cgroup.0).unwrap());
+    }
+
+    #[test]
+    fn survivors_of_a_gentle_policy_are_not_left_stopped() {
+        // The ignored SIGTERM carries over the exec
+        let mut child = Command::new("sh")
+            .args(["-c", "trap '' TERM; echo ready; exec sleep 60"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("failed to spawn sh");
+        let pid = child.id() as i32;
+        let mut ready = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut ready)
+            .unwrap();
+
+        let policy = KillPolicy {
+            steps: vec![KillStep::new(
+                Signal::SIGTERM,
+                time::Duration::from_millis(100),
+            )],
+            stop_first: true,
+            cont_before_term: false,
+        };
+        let outcome = kill_pid(pid, &policy, Safety::Enforce);
+        let state = procfs::process::Process::new(pid)
+            .and_then(|proc| proc.stat())
+            .map(|stat| stat.state);
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert!(outcome.is_ok_and(|outcome| !outcome.exited()));
+        assert_ne!(state.unwrap(), 'T');
+    }
 }
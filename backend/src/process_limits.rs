@@ -0,0 +1,314 @@
+//! Inspecting and changing resource limits of running processes via prlimit(2).
+
+use crate::safety::{self, Safety};
+use crate::types::ProcError;
+use nix::errno::Errno;
+use std::fmt;
+use std::ptr;
+
+/// A resource that can be limited with `setrlimit`/`prlimit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resource {
+    Cpu,
+    FileSize,
+    Data,
+    Stack,
+    Core,
+    Rss,
+    Nproc,
+    Nofile,
+    Memlock,
+    AddressSpace,
+    Locks,
+    SigPending,
+    MsgQueue,
+    Nice,
+    RtPrio,
+    RtTime,
+}
+
+/// What the values of a [`Resource`] are counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitUnit {
+    Bytes,
+    Seconds,
+    Microseconds,
+    Count,
+}
+
+impl Resource {
+    /// Every resource, in the order of `/proc/<pid>/limits`.
+    pub const ALL: [Resource; 16] = [
+        Resource::Cpu,
+        Resource::FileSize,
+        Resource::Data,
+        Resource::Stack,
+        Resource::Core,
+        Resource::Rss,
+        Resource::Nproc,
+        Resource::Nofile,
+        Resource::Memlock,
+        Resource::AddressSpace,
+        Resource::Locks,
+        Resource::SigPending,
+        Resource::MsgQueue,
+        Resource::Nice,
+        Resource::RtPrio,
+        Resource::RtTime,
+    ];
+
+    /// The `RLIMIT_*` constant name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Resource::Cpu => "RLIMIT_CPU",
+            Resource::FileSize => "RLIMIT_FSIZE",
+            Resource::Data => "RLIMIT_DATA",
+            Resource::Stack => "RLIMIT_STACK",
+            Resource::Core => "RLIMIT_CORE",
+            Resource::Rss => "RLIMIT_RSS",
+            Resource::Nproc => "RLIMIT_NPROC",
+            Resource::Nofile => "RLIMIT_NOFILE",
+            Resource::Memlock => "RLIMIT_MEMLOCK",
+            Resource::AddressSpace => "RLIMIT_AS",
+            Resource::Locks => "RLIMIT_LOCKS",
+            Resource::SigPending => "RLIMIT_SIGPENDING",
+            Resource::MsgQueue => "RLIMIT_MSGQUEUE",
+            Resource::Nice => "RLIMIT_NICE",
+            Resource::RtPrio => "RLIMIT_RTPRIO",
+            Resource::RtTime => "RLIMIT_RTTIME",
+        }
+    }
+
+    /// A short description, as in `/proc/<pid>/limits`.
+    pub fn description(self) -> &'static str {
+        match self {
+            Resource::Cpu => "Max CPU time",
+            Resource::FileSize => "Max file size",
+            Resource::Data => "Max data size",
+            Resource::Stack => "Max stack size",
+            Resource::Core => "Max core file size",
+            Resource::Rss => "Max resident set",
+            Resource::Nproc => "Max processes",
+            Resource::Nofile => "Max open files",
+            Resource::Memlock => "Max locked memory",
+            Resource::AddressSpace => "Max address space",
+            Resource::Locks => "Max file locks",
+            Resource::SigPending => "Max pending signals",
+            Resource::MsgQueue => "Max msgqueue size",
+            Resource::Nice => "Max nice priority",
+            Resource::RtPrio => "Max realtime priority",
+            Resource::RtTime => "Max realtime timeout",
+        }
+    }
+
+    /// What the values of this resource are counted in.
+    pub fn unit(self) -> LimitUnit {
+        match self {
+            Resource::Cpu => LimitUnit::Seconds,
+            Resource::RtTime => LimitUnit::Microseconds,
+            Resource::FileSize
+            | Resource::Data
+            | Resource::Stack
+            | Resource::Core
+            | Resource::Rss
+            | Resource::Memlock
+            | Resource::AddressSpace
+            | Resource::MsgQueue => LimitUnit::Bytes,
+            Resource::Nproc
+            | Resource::Nofile
+            | Resource::Locks
+            | Resource::SigPending
+            | Resource::Nice
+            | Resource::RtPrio => LimitUnit::Count,
+        }
+    }
+
+    fn raw(self) -> libc::c_int {
+        (match self {
+            Resource::Cpu => libc::RLIMIT_CPU,
+            Resource::FileSize => libc::RLIMIT_FSIZE,
+            Resource::Data => libc::RLIMIT_DATA,
+            Resource::Stack => libc::RLIMIT_STACK,
+            Resource::Core => libc::RLIMIT_CORE,
+            Resource::Rss => libc::RLIMIT_RSS,
+            Resource::Nproc => libc::RLIMIT_NPROC,
+            Resource::Nofile => libc::RLIMIT_NOFILE,
+            Resource::Memlock => libc::RLIMIT_MEMLOCK,
+            Resource::AddressSpace => libc::RLIMIT_AS,
+            Resource::Locks => libc::RLIMIT_LOCKS,
+            Resource::SigPending => libc::RLIMIT_SIGPENDING,
+            Resource::MsgQueue => libc::RLIMIT_MSGQUEUE,
+            Resource::Nice => libc::RLIMIT_NICE,
+            Resource::RtPrio => libc::RLIMIT_RTPRIO,
+            Resource::RtTime => libc::RLIMIT_RTTIME,
+        }) as libc::c_int
+    }
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// The soft and hard value of a resource limit; `None` means unlimited.
+///
+/// The kernel enforces the soft value. A process may raise its soft value up
+/// to the hard one, but raising the hard value needs `CAP_SYS_RESOURCE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limit {
+    pub soft: Option<u64>,
+    pub hard: Option<u64>,
+}
+
+impl Limit {
+    fn from_raw(raw: libc::rlimit64) -> Self {
+        let value = |v: u64| (v != libc::RLIM64_INFINITY).then_some(v);
+        Self {
+            soft: value(raw.rlim_cur),
+            hard: value(raw.rlim_max),
+        }
+    }
+
+    fn raw(self) -> libc::rlimit64 {
+        libc::rlimit64 {
+            rlim_cur: self.soft.unwrap_or(libc::RLIM64_INFINITY),
+            rlim_max: self.hard.unwrap_or(libc::RLIM64_INFINITY),
+        }
+    }
+}
+
+/// Every resource limit of `pid`.
+pub fn limits(pid: i32) -> Result<Vec<(Resource, Limit)>, ProcError> {
+    Resource::ALL
+        .iter()
+        .map(|&resource| Ok((resource, limit(pid, resource)?)))
+        .collect()
+}
+
+/// One resource limit of `pid`.
+pub fn limit(pid: i32, resource: Resource) -> Result<Limit, ProcError> {
+    let mut old = libc::rlimit64 {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    prlimit(pid, resource, None, Some(&mut old))?;
+    Ok(Limit::from_raw(old))
+}
+
+/// Change one resource limit of a running process, e.g. raise `RLIMIT_NOFILE`
+/// on a server that runs out of file descriptors.
+///
+/// Fails with [`ProcError::PermissionDenied`] when raising a hard value
+/// without `CAP_SYS_RESOURCE`, or for a process owned by another user.
+pub fn set_limit(
+    pid: i32,
+    resource: Resource,
+    limit: Limit,
+    safety: Safety,
+) -> Result<(), ProcError> {
+    let soft_above_hard = match (limit.soft, limit.hard) {
+        (_, None) => false,
+        (None, Some(_)) => true,
+        (Some(soft), Some(hard)) => soft > hard,
+    };
+    if soft_above_hard {
+        return Err(ProcError::LimitError(
+            pid,
+            format!("{}: the soft value is above the hard value", resource),
+        ));
+    }
+    safety::check(pid, safety)?;
+
+    prlimit(pid, resource, Some(&limit.raw()), None)
+}
+
+fn prlimit(
+    pid: i32,
+    resource: Resource,
+    new: Option<&libc::rlimit64>,
+    old: Option<&mut libc::rlimit64>,
+) -> Result<(), ProcError> {
+    let new = new.map_or(ptr::null(), |new| new as *const libc::rlimit64);
+    let old = old.map_or(ptr::null_mut(), |old| old as *mut libc::rlimit64);
+    // SAFETY: both pointers are either null or point to valid rlimit64 structs
+    let ret = unsafe { libc::syscall(libc::SYS_prlimit64, pid, resource.raw(), new, old) };
+    if ret == 0 {
+        return Ok(());
+    }
+
+    Err(match Errno::last() {
+        Errno::EPERM => ProcError::PermissionDenied(pid),
+        Errno::ESRCH => ProcError::NotFound(pid),
+        e => ProcError::LimitError(pid, format!("{}: {}", resource, e)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infinity_maps_to_none() {
+        let limit = Limit::from_raw(libc::rlimit64 {
+            rlim_cur: 1024,
+            rlim_max: libc::RLIM64_INFINITY,
+        });
+        assert_eq!(
+            limit,
+            Limit {
+                soft: Some(1024),
+                hard: None
+            }
+        );
+
+        let raw = limit.raw();
+        assert_eq!(raw.rlim_cur, 1024);
+        assert_eq!(raw.rlim_max, libc::RLIM64_INFINITY);
+        assert_eq!(Limit::from_raw(raw), limit);
+    }
+
+    #[test]
+    fn soft_above_hard_is_refused() {
+        let pid = std::process::id() as i32;
+        // Checked before the safety check, which would refuse our own process
+        for limit in [
+            Limit {
+                soft: Some(11),
+                hard: Some(10),
+            },
+            Limit {
+                soft: None,
+                hard: Some(10),
+            },
+        ] {
+            assert!(matches!(
+                set_limit(pid, Resource::Nofile, limit, Safety::Enforce),
+                Err(ProcError::LimitError(..))
+            ));
+        }
+    }
+
+    #[test]
+    fn limits_match_proc() {
+        let pid = std::process::id() as i32;
+        let limits = limits(pid).unwrap();
+        let proc_limits = std::fs::read_to_string("/proc/self/limits").unwrap();
+
+        // Rows are in the order of Resource::ALL, below a header
+        let rows: Vec<&str> = proc_limits.lines().skip(1).collect();
+        assert_eq!(rows.len(), limits.len());
+        for ((resource, limit), row) in limits.iter().zip(rows) {
+            // The description takes the first 25 columns
+            assert!(row
+                .to_lowercase()
+                .starts_with(&resource.description().to_lowercase()));
+            let mut values = row[26..].split_whitespace().map(|value| match value {
+                "unlimited" => None,
+                value => Some(value.parse::<u64>().unwrap()),
+            });
+            assert_eq!(values.next(), Some(limit.soft), "{}", resource);
+            assert_eq!(values.next(), Some(limit.hard), "{}", resource);
+        }
+    }
+}
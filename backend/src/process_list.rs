@@ -1,200 +1,516 @@
 //! Process listing functionality with real CPU calculation.
 
-use crate::types::{ProcError, ProcessInfo};
-use std::collections::HashMap;
+use crate::cgroup;
+use crate::process_io::{self, IoTracker};
+use crate::process_memory;
+use crate::process_monitor::ProcessMonitor;
+use crate::process_oom;
+use crate::process_sched::{self, SchedPolicy};
+use crate::types::{ProcError, ProcessInfo, Suspension};
+use nix::unistd::{Uid, User};
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::sync::Mutex;
-use std::time::Instant;
+use std::time::{Duration, SystemTime};
 
-// Global CPU tracking state
-static CPU_TRACKER: Mutex<Option<CpuTracker>> = Mutex::new(None);
+// Monitor behind `list_processes`, shared by all of its callers
+static SHARED_MONITOR: Mutex<Option<ProcessMonitor>> = Mutex::new(None);
 
-#[allow(dead_code)]
+/// How [`ProcessInfo::cpu_percent`] is expressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CpuMode {
+    /// Share of a single core, like `top` by default: a process keeping four
+    /// cores busy shows 400%.
+    #[default]
+    Irix,
+    /// Share of the whole machine, so all processes together add up to at
+    /// most 100%.
+    Solaris,
+}
+
+/// CPU time of a process as of the last listing.
 struct ProcessCpuData {
-    utime: u64,
-    stime: u64,
+    /// Start time in clock ticks after boot, to notice a reused PID.
+    start_time: u64,
+    /// User plus system time, in clock ticks.
     total_time: u64,
-    last_update: Instant,
+    cpu_percent: f32,
+}
+
+/// System-wide CPU time, read from `/proc/stat` once per listing.
+#[derive(Clone, Copy)]
+struct SystemSample {
+    /// Time spent by all CPUs, idle included, in clock ticks.
+    total: u64,
+    /// Number of online CPUs.
+    cpus: u64,
+    /// Time since boot, in clock ticks.
+    uptime: u64,
 }
 
-struct CpuTracker {
+/// CPU times seen at the previous listing, to measure usage against.
+pub(crate) struct CpuTracker {
     process_data: HashMap<i32, ProcessCpuData>,
-    last_system_total: u64,
-    last_system_idle: u64,
-    last_system_update: Instant,
+    /// Sample the percentages are measured from, `None` on the first listing.
+    previous: Option<SystemSample>,
+    current: Option<SystemSample>,
+    /// Whether the current listing took a new sample; if not, the previous
+    /// percentages still stand.
+    advanced: bool,
     clock_ticks_per_second: u64,
 }
 
 impl CpuTracker {
-    fn new() -> Self {
-        let clock_ticks_per_second = 100; // Standard Linux clock ticks per second
-        let (system_total, system_idle) = Self::read_system_cpu_times();
-
+    pub(crate) fn new() -> Self {
         Self {
             process_data: HashMap::new(),
-            last_system_total: system_total,
-            last_system_idle: system_idle,
-            last_system_update: Instant::now(),
-            clock_ticks_per_second,
+            previous: None,
+            current: None,
+            advanced: false,
+            clock_ticks_per_second: procfs::ticks_per_second(),
         }
     }
 
-    fn read_system_cpu_times() -> (u64, u64) {
-        // Read from /proc/stat to get system-wide CPU times
-        if let Ok(stat_content) = std::fs::read_to_string("/proc/stat") {
-            if let Some(cpu_line) = stat_content.lines().next() {
-                if cpu_line.starts_with("cpu ") {
-                    let values: Vec<u64> = cpu_line
-                        .split_whitespace()
-                        .skip(1) // Skip "cpu"
-                        .take(8) // user, nice, system, idle, iowait, irq, softirq, steal
-                        .filter_map(|s| s.parse().ok())
-                        .collect();
-
-                    if values.len() >= 4 {
-                        let total = values.iter().sum();
-                        let idle = values[3]; // idle time
-                        return (total, idle);
-                    }
-                }
-            }
+    /// Take the system-wide sample for a new listing.
+    ///
+    /// If not a single clock tick has passed since the last listing there is
+    /// nothing to measure, so the previous percentages are kept instead.
+    pub(crate) fn begin_listing(&mut self) {
+        let sample = self.read_system_sample();
+        self.begin_listing_at(sample);
+    }
+
+    fn begin_listing_at(&mut self, sample: Option<SystemSample>) {
+        self.advanced = false;
+        let Some(sample) = sample else {
+            return;
+        };
+        if self
+            .current
+            .is_some_and(|current| sample.total <= current.total)
+        {
+            return;
         }
-        (0, 0)
+        self.previous = self.current.replace(sample);
+        self.advanced = true;
     }
 
-    fn calculate_cpu_percent(&mut self, pid: i32, utime: u64, stime: u64) -> f32 {
-        let now = Instant::now();
-        let total_time = utime + stime;
+    fn read_system_sample(&self) -> Option<SystemSample> {
+        let stat = std::fs::read_to_string("/proc/stat").ok()?;
+        let mut lines = stat.lines();
+        let total = lines
+            .next()?
+            .strip_prefix("cpu ")?
+            .split_whitespace()
+            .take(8) // user, nice, system, idle, iowait, irq, softirq, steal
+            .filter_map(|s| s.parse::<u64>().ok())
+            .sum();
+        let cpus = lines.take_while(|line| line.starts_with("cpu")).count() as u64;
 
-        // Update system CPU times
-        let (current_system_total, current_system_idle) = Self::read_system_cpu_times();
+        let uptime = read_uptime()?;
 
-        if let Some(prev_data) = self.process_data.get(&pid) {
-            let elapsed_seconds = now.duration_since(prev_data.last_update).as_secs_f64();
-            let system_elapsed_seconds = now.duration_since(self.last_system_update).as_secs_f64();
+        Some(SystemSample {
+            total,
+            cpus: cpus.max(1),
+            uptime: (uptime * self.clock_ticks_per_second as f64) as u64,
+        })
+    }
 
-            // Need at least 1 second for meaningful measurement
-            if elapsed_seconds >= 1.0 && system_elapsed_seconds >= 1.0 {
-                // Calculate process CPU time difference
-                let process_time_diff = total_time.saturating_sub(prev_data.total_time);
-                let process_cpu_seconds =
-                    process_time_diff as f64 / self.clock_ticks_per_second as f64;
+    /// CPU usage of a process since the previous listing, given its start
+    /// time and total CPU time in clock ticks.
+    pub(crate) fn calculate_cpu_percent(
+        &mut self,
+        pid: i32,
+        start_time: u64,
+        total_time: u64,
+        mode: CpuMode,
+    ) -> f32 {
+        let (Some(previous), Some(current)) = (self.previous, self.current) else {
+            self.remember(pid, start_time, total_time, 0.0);
+            return 0.0;
+        };
 
-                // Calculate system CPU time difference
-                let system_total_diff = current_system_total.saturating_sub(self.last_system_total);
-                let system_cpu_seconds =
-                    system_total_diff as f64 / self.clock_ticks_per_second as f64;
+        let known = self
+            .process_data
+            .get(&pid)
+            .filter(|data| data.start_time == start_time);
+        let previous_time = match known {
+            Some(data) if !self.advanced => return data.cpu_percent,
+            Some(data) => Some(data.total_time),
+            // Started since the previous sample, so all its CPU time is new
+            None if start_time >= previous.uptime => Some(0),
+            None => None,
+        };
 
-                // CPU percentage = (process CPU time / system CPU time) * 100
-                let cpu_percent = if system_cpu_seconds > 0.0 {
-                    (process_cpu_seconds / system_cpu_seconds) * 100.0
-                } else {
-                    // Fallback: simple time-based calculation
-                    (process_cpu_seconds / elapsed_seconds) * 100.0
-                };
-
-                // Update stored data
-                self.process_data.insert(
-                    pid,
-                    ProcessCpuData {
-                        utime,
-                        stime,
-                        total_time,
-                        last_update: now,
-                    },
-                );
-
-                // Update system times if this is a fresh measurement
-                if system_elapsed_seconds >= 1.0 {
-                    self.last_system_total = current_system_total;
-                    self.last_system_idle = current_system_idle;
-                    self.last_system_update = now;
+        let cpu_percent = match previous_time {
+            Some(previous_time) => {
+                let used = total_time.saturating_sub(previous_time) as f64;
+                let elapsed = current.total.saturating_sub(previous.total).max(1) as f64;
+                // Tick rounding can push a busy process slightly past the
+                // maximum
+                let share = (used / elapsed * 100.0).min(100.0);
+                match mode {
+                    CpuMode::Irix => share * current.cpus as f64,
+                    CpuMode::Solaris => share,
                 }
-
-                // Return reasonable CPU percentage
-                (cpu_percent.min(100.0).max(0.0)) as f32
-            } else {
-                // Not enough time elapsed, return 0
-                0.0
             }
-        } else {
-            // First time seeing this process, store data but return 0
-            self.process_data.insert(
-                pid,
-                ProcessCpuData {
-                    utime,
-                    stime,
-                    total_time,
-                    last_update: now,
-                },
-            );
-            0.0
-        }
+            None => 0.0,
+        } as f32;
+
+        self.remember(pid, start_time, total_time, cpu_percent);
+        cpu_percent
+    }
+
+    fn remember(&mut self, pid: i32, start_time: u64, total_time: u64, cpu_percent: f32) {
+        self.process_data.insert(
+            pid,
+            ProcessCpuData {
+                start_time,
+                total_time,
+                cpu_percent,
+            },
+        );
     }
 
-    fn cleanup_old_processes(&mut self, current_pids: &[i32]) {
-        let current_pids_set: std::collections::HashSet<i32> =
-            current_pids.iter().cloned().collect();
-        self.process_data
-            .retain(|pid, _| current_pids_set.contains(pid));
+    pub(crate) fn cleanup_old_processes(&mut self, current_pids: &[i32]) {
+        retain_current(&mut self.process_data, current_pids);
     }
 }
 
+/// Forget what a tracker kept about processes that are no longer listed.
+pub(crate) fn retain_current<T>(process_data: &mut HashMap<i32, T>, current_pids: &[i32]) {
+    let current_pids: HashSet<i32> = current_pids.iter().copied().collect();
+    process_data.retain(|pid, _| current_pids.contains(pid));
+}
+
+/// Seconds since boot, from `/proc/uptime`.
+pub(crate) fn read_uptime() -> Option<f64> {
+    std::fs::read_to_string("/proc/uptime")
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
 /// List all processes with their info including real CPU usage.
 /// Returns a vector of `ProcessInfo`.
+///
+/// CPU usage is measured between consecutive calls and expressed per core
+/// ([`CpuMode::Irix`]), so it reads 0% on the first call. All callers share
+/// one [`ProcessMonitor`], so two consumers calling this in turn each see
+/// usage since the other's call; give each consumer its own monitor instead,
+/// which also lets it choose its [`CpuMode`].
 pub fn list_processes() -> Result<Vec<ProcessInfo>, ProcError> {
+    let mut monitor = SHARED_MONITOR.lock().unwrap();
+    let monitor = monitor.get_or_insert_with(ProcessMonitor::new);
+    monitor.refresh().map(|snapshot| snapshot.processes)
+}
+
+/// List all processes, measuring CPU usage against the previous listing made
+/// with `tracker` and I/O rates against the one made with `io_tracker`.
+/// `detailed_memory` also reads each process's
+/// [`MemoryDetails`](process_memory::MemoryDetails), which is much slower, and
+/// `detailed_scheduling` its CPU affinity and I/O priority.
+pub(crate) fn collect_processes(
+    tracker: &mut CpuTracker,
+    io_tracker: &mut IoTracker,
+    mode: CpuMode,
+    detailed_memory: bool,
+    detailed_scheduling: bool,
+) -> Result<Vec<ProcessInfo>, ProcError> {
     let mut processes = Vec::new();
 
     // Access the /proc directory using procfs
     let all_procs = procfs::process::all_processes()
         .map_err(|e| ProcError::Other(format!("Failed to read /proc: {}", e)))?;
 
-    // Initialize CPU tracker if needed
-    {
-        let mut tracker_guard = CPU_TRACKER.lock().unwrap();
-        if tracker_guard.is_none() {
-            *tracker_guard = Some(CpuTracker::new());
+    // One system-wide CPU sample for the whole listing
+    tracker.begin_listing();
+    io_tracker.begin_listing();
+    let page_size = procfs::page_size();
+
+    let mut current_pids = Vec::new();
+    let frozen = frozen_pids();
+    let mut user_names = HashMap::new();
+    let boot_time = procfs::boot_time_secs().unwrap_or(0);
+    let now = SystemTime::now();
+
+    for proc in all_procs.flatten() {
+        if let Ok(stat) = proc.stat() {
+            current_pids.push(stat.pid);
+
+            let memory_bytes = proc.statm().map(|m| m.resident * page_size).unwrap_or(0);
+            let cpu_percent = tracker.calculate_cpu_percent(
+                stat.pid,
+                stat.starttime,
+                stat.utime + stat.stime,
+                mode,
+            );
+            // Only readable for processes we could attach a debugger to
+            let io = process_io::io_stats(stat.pid).ok();
+            let io_rates = io.map(|io| io_tracker.calculate_rates(stat.pid, stat.starttime, io));
+
+            let (uid, euid) = proc
+                .status()
+                .map(|status| (status.ruid, status.euid))
+                .or_else(|_| proc.uid().map(|uid| (uid, uid)))
+                .unwrap_or((0, 0));
+            let start_time = SystemTime::UNIX_EPOCH
+                + Duration::from_secs(boot_time)
+                + Duration::from_secs_f64(
+                    stat.starttime as f64 / tracker.clock_ticks_per_second as f64,
+                );
+
+            let process_info = ProcessInfo {
+                pid: stat.pid,
+                name: stat.comm.clone(),
+                cmdline: proc.cmdline().unwrap_or_default(),
+                exe: proc.exe().ok(),
+                cwd: proc.cwd().ok(),
+                uid,
+                user: user_name(uid, &mut user_names),
+                euid,
+                effective_user: user_name(euid, &mut user_names),
+                start_time,
+                age: now.duration_since(start_time).unwrap_or_default(),
+                threads: stat.num_threads as u32,
+                cpu_percent,
+                memory_bytes,
+                memory_details: if detailed_memory {
+                    process_memory::memory_details(stat.pid).ok()
+                } else {
+                    None
+                },
+                io,
+                io_rates,
+                state: stat.state.to_string(),
+                ppid: stat.ppid,
+                pgid: stat.pgrp,
+                sid: stat.session,
+                suspended: suspension(stat.pid, stat.state, &frozen),
+                nice: stat.nice as i32,
+                priority: stat.priority as i32,
+                sched_policy: SchedPolicy::from_raw(
+                    stat.policy.unwrap_or(0),
+                    stat.rt_priority.unwrap_or(0),
+                ),
+                cpu_affinity: if detailed_scheduling {
+                    process_sched::cpu_affinity(stat.pid).ok()
+                } else {
+                    None
+                },
+                io_priority: if detailed_scheduling {
+                    process_sched::io_priority(stat.pid).ok()
+                } else {
+                    None
+                },
+                oom_score: process_oom::oom_score(stat.pid).unwrap_or(0),
+                oom_score_adj: process_oom::oom_score_adj(stat.pid).unwrap_or(0),
+            };
+            processes.push(process_info);
         }
     }
 
-    let mut current_pids = Vec::new();
+    // Clean up old process data
+    tracker.cleanup_old_processes(&current_pids);
+    io_tracker.cleanup_old_processes(&current_pids);
 
-    for proc_result in all_procs {
-        if let Ok(proc) = proc_result {
-            if let Ok(stat) = proc.stat() {
-                current_pids.push(stat.pid);
-
-                let memory_bytes = proc.statm().map(|m| m.resident * 4096).unwrap_or(0);
-
-                // Calculate real CPU percentage
-                let cpu_percent = {
-                    let mut tracker_guard = CPU_TRACKER.lock().unwrap();
-                    if let Some(ref mut tracker) = tracker_guard.as_mut() {
-                        tracker.calculate_cpu_percent(stat.pid, stat.utime, stat.stime)
-                    } else {
-                        0.0
-                    }
-                };
-
-                let process_info = ProcessInfo {
-                    pid: stat.pid,
-                    name: stat.comm.clone(),
-                    cpu_percent,
-                    memory_bytes,
-                    state: stat.state.to_string(),
-                    ppid: stat.ppid,
-                };
-                processes.push(process_info);
+    Ok(processes)
+}
+
+/// Name of the user `uid`, or the UID itself if it has no entry. `cache`
+/// keeps the names already looked up during this listing.
+fn user_name(uid: u32, cache: &mut HashMap<u32, String>) -> String {
+    cache
+        .entry(uid)
+        .or_insert_with(|| match User::from_uid(Uid::from_raw(uid)) {
+            Ok(Some(user)) => user.name,
+            _ => uid.to_string(),
+        })
+        .clone()
+}
+
+/// Work out whether `pid` is paused, given the PIDs in frozen cgroups.
+fn suspension(pid: i32, state: char, frozen: &HashSet<i32>) -> Option<Suspension> {
+    // Frozen tasks sleep in the freezer and do not show up as 'T'
+    if frozen.contains(&pid) {
+        return Some(Suspension::Frozen);
+    }
+
+    (state == 'T').then_some(Suspension::Stopped)
+}
+
+/// The PIDs in frozen cgroups, found with one walk of the cgroup tree rather
+/// than by reading the cgroup of every process. Nothing below a frozen cgroup
+/// needs looking at, as its descendants are frozen with it.
+fn frozen_pids() -> HashSet<i32> {
+    let mut frozen = HashSet::new();
+    let Ok(mount) = cgroup::mount_point() else {
+        return frozen;
+    };
+
+    let mut pending = vec![mount];
+    while let Some(dir) = pending.pop() {
+        if cgroup::is_frozen(&dir).unwrap_or(false) {
+            frozen.extend(cgroup::procs_recursive(&dir).unwrap_or_default());
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                pending.push(entry.path());
             }
         }
     }
 
-    // Clean up old process data
-    {
-        let mut tracker_guard = CPU_TRACKER.lock().unwrap();
-        if let Some(ref mut tracker) = tracker_guard.as_mut() {
-            tracker.cleanup_old_processes(&current_pids);
+    frozen
+}
+
+/// Map every PID to the PIDs of its direct children, built from the PPID
+/// field of each `/proc/<pid>/stat`.
+pub(crate) fn read_children_map() -> Result<HashMap<i32, Vec<i32>>, ProcError> {
+    let all_procs = procfs::process::all_processes()
+        .map_err(|e| ProcError::Other(format!("Failed to read /proc: {}", e)))?;
+
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    for proc in all_procs.flatten() {
+        if let Ok(stat) = proc.stat() {
+            children.entry(stat.ppid).or_default().push(stat.pid);
         }
     }
 
-    Ok(processes)
+    Ok(children)
+}
+
+/// Resident memory of `pid` in bytes, or 0 if it cannot be read.
+pub(crate) fn resident_bytes(pid: i32) -> u64 {
+    procfs::process::Process::new(pid)
+        .and_then(|proc| proc.statm())
+        .map(|m| m.resident * procfs::page_size())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Start a listing at `total` ticks of CPU time on 4 CPUs, `uptime` ticks
+    /// after boot.
+    fn listing(tracker: &mut CpuTracker, total: u64, uptime: u64) {
+        tracker.begin_listing_at(Some(SystemSample {
+            total,
+            cpus: 4,
+            uptime,
+        }));
+    }
+
+    #[test]
+    fn cpu_percent_is_zero_on_the_first_listing() {
+        let mut tracker = CpuTracker::new();
+        listing(&mut tracker, 1000, 5000);
+        assert_eq!(
+            tracker.calculate_cpu_percent(10, 100, 50, CpuMode::Irix),
+            0.0
+        );
+    }
+
+    #[test]
+    fn cpu_percent_per_core_and_per_machine() {
+        let mut irix = CpuTracker::new();
+        let mut solaris = CpuTracker::new();
+        for tracker in [&mut irix, &mut solaris] {
+            listing(tracker, 1000, 5000);
+        }
+        irix.calculate_cpu_percent(10, 100, 50, CpuMode::Irix);
+        solaris.calculate_cpu_percent(10, 100, 50, CpuMode::Solaris);
+
+        // 400 ticks passed across all 4 CPUs and the process used 100 of them,
+        // so a whole core
+        for tracker in [&mut irix, &mut solaris] {
+            listing(tracker, 1400, 5100);
+        }
+        assert_eq!(
+            irix.calculate_cpu_percent(10, 100, 150, CpuMode::Irix),
+            100.0
+        );
+        assert_eq!(
+            solaris.calculate_cpu_percent(10, 100, 150, CpuMode::Solaris),
+            25.0
+        );
+
+        // Tick rounding never takes a process past every CPU
+        for tracker in [&mut irix, &mut solaris] {
+            listing(tracker, 1500, 5125);
+        }
+        assert_eq!(
+            irix.calculate_cpu_percent(10, 100, 260, CpuMode::Irix),
+            400.0
+        );
+        assert_eq!(
+            solaris.calculate_cpu_percent(10, 100, 260, CpuMode::Solaris),
+            100.0
+        );
+    }
+
+    #[test]
+    fn cpu_percent_is_kept_when_no_time_passed() {
+        let mut tracker = CpuTracker::new();
+        listing(&mut tracker, 1000, 5000);
+        tracker.calculate_cpu_percent(10, 100, 50, CpuMode::Irix);
+        listing(&mut tracker, 1400, 5100);
+        assert_eq!(
+            tracker.calculate_cpu_percent(10, 100, 100, CpuMode::Irix),
+            50.0
+        );
+
+        listing(&mut tracker, 1400, 5100);
+        assert_eq!(
+            tracker.calculate_cpu_percent(10, 100, 120, CpuMode::Irix),
+            50.0
+        );
+    }
+
+    #[test]
+    fn cpu_percent_counts_a_reused_pid_as_a_new_process() {
+        let mut tracker = CpuTracker::new();
+        listing(&mut tracker, 1000, 5000);
+        tracker.calculate_cpu_percent(10, 100, 5000, CpuMode::Irix);
+
+        // PID 10 now belongs to a process started after the previous listing,
+        // so all 40 of its ticks are new rather than below the old 5000
+        listing(&mut tracker, 1400, 5100);
+        assert_eq!(
+            tracker.calculate_cpu_percent(10, 5050, 40, CpuMode::Irix),
+            40.0
+        );
+
+        // A process that was already running but never seen has no baseline
+        assert_eq!(
+            tracker.calculate_cpu_percent(11, 4000, 40, CpuMode::Irix),
+            0.0
+        );
+        listing(&mut tracker, 1800, 5200);
+        assert_eq!(
+            tracker.calculate_cpu_percent(11, 4000, 80, CpuMode::Irix),
+            40.0
+        );
+    }
+
+    #[test]
+    fn cleanup_forgets_exited_processes() {
+        let mut tracker = CpuTracker::new();
+        listing(&mut tracker, 1000, 5000);
+        tracker.calculate_cpu_percent(10, 100, 50, CpuMode::Irix);
+        tracker.cleanup_old_processes(&[]);
+
+        // Without its earlier CPU time, the process has no baseline
+        listing(&mut tracker, 1400, 5100);
+        assert_eq!(
+            tracker.calculate_cpu_percent(10, 100, 150, CpuMode::Irix),
+            0.0
+        );
+    }
 }
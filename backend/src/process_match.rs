@@ -0,0 +1,326 @@
+//! pkill-style selection of processes by name, owner, parent, terminal,
+//! cgroup and age, with a dry run that only reports what would be killed.
+
+use crate::cgroup;
+use crate::kill_policy::KillPolicy;
+use crate::pidfd::ProcessHandle;
+use crate::process_kill::escalate;
+use crate::process_list::read_uptime;
+use crate::safety::{self, Safety};
+use crate::types::{PidOutcome, ProcError};
+use nix::sys::signal::Signal;
+use nix::unistd::{getpid, User};
+use regex::Regex;
+use std::time::Duration;
+
+/// Which processes to match. Every criterion that is set must hold. Like
+/// `pkill`, at least one must be set: an empty `MatchCriteria` is refused
+/// rather than matching every process.
+#[derive(Debug, Clone, Default)]
+pub struct MatchCriteria {
+    /// Regex searched for in the command name (`comm`), like `pkill`.
+    /// Anchor it (`^name$`) to match the exact name.
+    pub name: Option<String>,
+    /// Regex searched for in the full command line, arguments joined by spaces,
+    /// like `pkill -f`.
+    pub cmdline: Option<String>,
+    /// Owner, as a user name or a numeric UID.
+    pub user: Option<String>,
+    /// PID of the direct parent.
+    pub parent: Option<i32>,
+    /// Controlling terminal, e.g. `pts/3` or `/dev/tty1`.
+    pub tty: Option<String>,
+    /// Cgroup v2 path relative to the cgroup root; its child cgroups match too.
+    pub cgroup: Option<String>,
+    /// Only match processes that have been running at least this long.
+    pub min_age: Option<Duration>,
+}
+
+impl MatchCriteria {
+    /// Whether no criterion is set.
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.cmdline.is_none()
+            && self.user.is_none()
+            && self.parent.is_none()
+            && self.tty.is_none()
+            && self.cgroup.is_none()
+            && self.min_age.is_none()
+    }
+}
+
+/// A process selected by [`MatchCriteria`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedProcess {
+    pub pid: i32,
+    pub name: String,
+    pub cmdline: String,
+    pub uid: u32,
+    /// Start time in clock ticks after boot, used to make sure the PID still
+    /// belongs to the same process when it is killed.
+    pub start_time: u64,
+}
+
+/// What [`kill_matching`] matched and, unless it was a dry run, how each kill went.
+#[derive(Debug)]
+pub struct MatchReport {
+    pub matched: Vec<MatchedProcess>,
+    /// One outcome per matched process; empty for a dry run.
+    pub outcomes: Vec<PidOutcome>,
+}
+
+/// Find every process that matches `criteria`, never including the caller.
+/// Fails if `criteria` is empty.
+pub fn find_matching(criteria: &MatchCriteria) -> Result<Vec<MatchedProcess>, ProcError> {
+    if criteria.is_empty() {
+        return Err(ProcError::Other(
+            "No match criteria given; at least one is needed".to_string(),
+        ));
+    }
+    let name = compile(criteria.name.as_deref())?;
+    let cmdline = compile(criteria.cmdline.as_deref())?;
+    let uid = criteria.user.as_deref().map(resolve_user).transpose()?;
+    let tty = criteria
+        .tty
+        .as_deref()
+        .map(|tty| tty.trim_start_matches("/dev/").to_string());
+    let cgroup = criteria
+        .cgroup
+        .as_deref()
+        .map(|path| format!("/{}", path.trim_matches('/')));
+    let uptime = criteria
+        .min_age
+        .map(|_| {
+            read_uptime()
+                .ok_or_else(|| ProcError::ProcfsError("Failed to read /proc/uptime".to_string()))
+        })
+        .transpose()?;
+
+    let own_pid = getpid().as_raw();
+    let all_procs = procfs::process::all_processes()
+        .map_err(|e| ProcError::Other(format!("Failed to read /proc: {}", e)))?;
+
+    let mut matched = Vec::new();
+    for proc in all_procs.flatten() {
+        // Processes can exit at any point; skip those that vanish
+        let Ok(stat) = proc.stat() else {
+            continue;
+        };
+        if stat.pid == own_pid {
+            continue;
+        }
+
+        if name.as_ref().is_some_and(|re| !re.is_match(&stat.comm)) {
+            continue;
+        }
+        if criteria.parent.is_some_and(|parent| parent != stat.ppid) {
+            continue;
+        }
+        if let Some(tty) = &tty {
+            if tty_name(stat.tty_nr()).as_ref() != Some(tty) {
+                continue;
+            }
+        }
+        if let (Some(min_age), Some(uptime)) = (criteria.min_age, uptime) {
+            let started = stat.starttime as f64 / procfs::ticks_per_second() as f64;
+            if uptime - started < min_age.as_secs_f64() {
+                continue;
+            }
+        }
+
+        let Ok(proc_uid) = proc.uid() else {
+            continue;
+        };
+        if uid.is_some_and(|uid| uid != proc_uid) {
+            continue;
+        }
+
+        let full_cmdline = proc.cmdline().unwrap_or_default().join(" ");
+        if cmdline
+            .as_ref()
+            .is_some_and(|re| !re.is_match(&full_cmdline))
+        {
+            continue;
+        }
+
+        if let Some(wanted) = &cgroup {
+            let Ok(path) = cgroup::cgroup_of(stat.pid) else {
+                continue;
+            };
+            if !cgroup_contains(wanted, &path) {
+                continue;
+            }
+        }
+
+        matched.push(MatchedProcess {
+            pid: stat.pid,
+            name: stat.comm,
+            cmdline: full_cmdline,
+            uid: proc_uid,
+            start_time: stat.starttime,
+        });
+    }
+
+    Ok(matched)
+}
+
+/// Kill every process that matches `criteria` with `policy`, like `pkill`.
+///
+/// With `dry_run` nothing is touched and the report only lists what would be
+/// killed, so it can be shown for confirmation and then passed to
+/// [`kill_matched`]. Unless `safety` overrides it, nothing is killed if any
+/// matched process is protected.
+pub fn kill_matching(
+    criteria: &MatchCriteria,
+    policy: &KillPolicy,
+    dry_run: bool,
+    safety: Safety,
+) -> Result<MatchReport, ProcError> {
+    let matched = find_matching(criteria)?;
+    let outcomes = if dry_run {
+        Vec::new()
+    } else {
+        kill_matched(&matched, policy, safety)?
+    };
+
+    Ok(MatchReport { matched, outcomes })
+}
+
+/// Kill exactly the processes of an earlier [`find_matching`] or dry run.
+///
+/// A process that has exited since, or whose PID now belongs to another
+/// process, is reported as [`ProcError::NotFound`] instead of being killed.
+/// All processes go through each step of `policy` together, so the whole set
+/// shares each grace period.
+pub fn kill_matched(
+    matched: &[MatchedProcess],
+    policy: &KillPolicy,
+    safety: Safety,
+) -> Result<Vec<PidOutcome>, ProcError> {
+    for process in matched {
+        safety::check(process.pid, safety)?;
+    }
+
+    let mut outcomes = Vec::new();
+    let mut handles = Vec::new();
+    for process in matched {
+        match open_same(process) {
+            Some(handle) => handles.push(handle),
+            None => outcomes.push(PidOutcome {
+                pid: process.pid,
+                result: Err(ProcError::NotFound(process.pid)),
+            }),
+        }
+    }
+
+    if policy.stop_first {
+        for handle in &handles {
+            let _ = handle.signal(Signal::SIGSTOP);
+        }
+    }
+    outcomes.extend(escalate(handles, policy, |_| Ok(false)));
+
+    Ok(outcomes)
+}
+
+/// Open a handle on `process`, or `None` if its PID no longer belongs to it.
+fn open_same(process: &MatchedProcess) -> Option<ProcessHandle> {
+    // Once the handle is open the PID cannot be reused, so checking the start
+    // time afterwards is enough
+    let handle = ProcessHandle::open(process.pid).ok()?;
+    let stat = procfs::process::Process::new(process.pid)
+        .and_then(|proc| proc.stat())
+        .ok()?;
+    (stat.starttime == process.start_time).then_some(handle)
+}
+
+fn compile(pattern: Option<&str>) -> Result<Option<Regex>, ProcError> {
+    pattern
+        .map(|pattern| {
+            Regex::new(pattern)
+                .map_err(|e| ProcError::Other(format!("Invalid pattern {:?}: {}", pattern, e)))
+        })
+        .transpose()
+}
+
+/// Resolve a user name or numeric UID to a UID.
+fn resolve_user(user: &str) -> Result<u32, ProcError> {
+    if let Ok(uid) = user.parse() {
+        return Ok(uid);
+    }
+    match User::from_name(user) {
+        Ok(Some(user)) => Ok(user.uid.as_raw()),
+        _ => Err(ProcError::Other(format!("Unknown user {:?}", user))),
+    }
+}
+
+/// Whether cgroup `path` is `wanted` or one of its descendants. Both are
+/// absolute, without a trailing slash, so `/a` holds `/a/b` but not `/ab`.
+fn cgroup_contains(wanted: &str, path: &str) -> bool {
+    wanted == "/" || path == wanted || path.starts_with(&format!("{}/", wanted))
+}
+
+/// Name of a terminal under `/dev` from its major and minor number, as found
+/// in the `tty_nr` field of `/proc/<pid>/stat`.
+fn tty_name((major, minor): (i32, i32)) -> Option<String> {
+    match major {
+        0 => None,
+        4 if minor < 64 => Some(format!("tty{}", minor)),
+        4 => Some(format!("ttyS{}", minor - 64)),
+        // Unix98 pseudo-terminals span majors 136 to 143
+        136..=143 => Some(format!("pts/{}", (major - 136) * 256 + minor)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_criteria_are_refused() {
+        let criteria = MatchCriteria::default();
+        assert!(criteria.is_empty());
+        assert!(find_matching(&criteria).is_err());
+        assert!(kill_matching(&criteria, &KillPolicy::default(), false, Safety::Enforce).is_err());
+    }
+
+    #[test]
+    fn tty_name_follows_the_device_numbers() {
+        assert_eq!(tty_name((0, 0)), None);
+        assert_eq!(tty_name((4, 1)).as_deref(), Some("tty1"));
+        assert_eq!(tty_name((4, 63)).as_deref(), Some("tty63"));
+        assert_eq!(tty_name((4, 64)).as_deref(), Some("ttyS0"));
+        assert_eq!(tty_name((4, 65)).as_deref(), Some("ttyS1"));
+        assert_eq!(tty_name((136, 0)).as_deref(), Some("pts/0"));
+        assert_eq!(tty_name((136, 255)).as_deref(), Some("pts/255"));
+        assert_eq!(tty_name((137, 0)).as_deref(), Some("pts/256"));
+        assert_eq!(tty_name((143, 255)).as_deref(), Some("pts/2047"));
+        assert_eq!(tty_name((144, 0)), None);
+    }
+
+    #[test]
+    fn cgroup_contains_only_whole_path_components() {
+        assert!(cgroup_contains("/a", "/a"));
+        assert!(cgroup_contains("/a", "/a/b"));
+        assert!(!cgroup_contains("/a", "/ab"));
+        assert!(!cgroup_contains("/a/b", "/a"));
+        assert!(cgroup_contains("/", "/anything/at/all"));
+    }
+
+    #[test]
+    fn invalid_patterns_are_reported() {
+        assert!(matches!(compile(None), Ok(None)));
+        assert!(matches!(compile(Some("^fire(fox)?$")), Ok(Some(_))));
+        match compile(Some("fire(fox")) {
+            Err(ProcError::Other(message)) => assert!(message.contains("fire(fox")),
+            _ => panic!("an unclosed group must not compile"),
+        }
+
+        let criteria = MatchCriteria {
+            name: Some("[".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(find_matching(&criteria), Err(ProcError::Other(_))));
+    }
+}
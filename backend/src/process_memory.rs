@@ -0,0 +1,132 @@
+//! Detailed memory accounting from `/proc/<pid>/smaps_rollup`, which splits
+//! resident memory into what a process shares and what is its own.
+
+use crate::types::ProcError;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Memory use of a process beyond its resident set size, in bytes.
+///
+/// Unlike RSS these add up sensibly across processes: summing `pss` over all
+/// processes gives the memory actually in use, as shared pages are divided
+/// between the processes mapping them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryDetails {
+    /// Proportional set size: private memory plus a fair share of shared memory.
+    pub pss: u64,
+    /// Unique set size: memory only this process uses, which is what killing
+    /// it would free.
+    pub uss: u64,
+    /// Resident memory also mapped by other processes.
+    pub shared: u64,
+    /// Memory swapped out.
+    pub swap: u64,
+    /// Proportional share of swapped-out memory, like `pss` for swap.
+    pub swap_pss: u64,
+}
+
+/// Read the detailed memory use of `pid`.
+///
+/// This walks every mapping of the process in the kernel, so it costs far more
+/// than the resident size from `statm`. It also needs the same permission as
+/// attaching a debugger, so it usually fails for other users' processes.
+/// Kernel threads read as all zeros.
+pub fn memory_details(pid: i32) -> Result<MemoryDetails, ProcError> {
+    let path = format!("/proc/{}/smaps_rollup", pid);
+    let rollup = match fs::read_to_string(&path) {
+        Ok(rollup) => rollup,
+        // Kernel threads have no memory of their own and fail with ESRCH
+        Err(e)
+            if e.raw_os_error() == Some(libc::ESRCH)
+                && Path::new(&format!("/proc/{}", pid)).exists() =>
+        {
+            return Ok(MemoryDetails::default());
+        }
+        Err(e) => {
+            return Err(match e.kind() {
+                io::ErrorKind::NotFound => ProcError::NotFound(pid),
+                io::ErrorKind::PermissionDenied => ProcError::PermissionDenied(pid),
+                _ if e.raw_os_error() == Some(libc::ESRCH) => ProcError::NotFound(pid),
+                _ => ProcError::Other(format!("Failed to read {}: {}", path, e)),
+            })
+        }
+    };
+
+    Ok(parse_smaps_rollup(&rollup))
+}
+
+/// Parse the contents of a `smaps_rollup` file. Unknown lines are ignored.
+fn parse_smaps_rollup(rollup: &str) -> MemoryDetails {
+    let mut details = MemoryDetails::default();
+    for line in rollup.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Every value is given in kB
+        let Some(kib) = value
+            .trim()
+            .strip_suffix("kB")
+            .and_then(|kib| kib.trim().parse::<u64>().ok())
+        else {
+            continue;
+        };
+        let bytes = kib * 1024;
+        match key {
+            "Pss" => details.pss = bytes,
+            "Private_Clean" | "Private_Dirty" => details.uss += bytes,
+            "Shared_Clean" | "Shared_Dirty" => details.shared += bytes,
+            "Swap" => details.swap = bytes,
+            "SwapPss" => details.swap_pss = bytes,
+            _ => {}
+        }
+    }
+
+    details
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_smaps_rollup_sums_private_and_shared_pages() {
+        let rollup = "\
+00400000-7ffd6b9ff000 ---p 00000000 00:00 0                              [rollup]
+Rss:               12000 kB
+Pss:                7000 kB
+Pss_Anon:           4000 kB
+Shared_Clean:       4000 kB
+Shared_Dirty:       1000 kB
+Private_Clean:      2000 kB
+Private_Dirty:      5000 kB
+Referenced:        11000 kB
+Anonymous:          5000 kB
+Swap:                300 kB
+SwapPss:             100 kB
+Locked:                0 kB
+";
+        assert_eq!(
+            parse_smaps_rollup(rollup),
+            MemoryDetails {
+                pss: 7000 * 1024,
+                uss: 7000 * 1024,
+                shared: 5000 * 1024,
+                swap: 300 * 1024,
+                swap_pss: 100 * 1024,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_smaps_rollup_skips_malformed_lines() {
+        let rollup = "Pss: lots\nSwap: 12\nUss 5 kB\nPrivate_Dirty:  8 kB\n";
+        assert_eq!(
+            parse_smaps_rollup(rollup),
+            MemoryDetails {
+                uss: 8 * 1024,
+                ..MemoryDetails::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Instance-based process sampling, so that independent consumers each
+//! measure CPU usage over their own refresh interval.
+
+use crate::process_io::IoTracker;
+use crate::process_list::{collect_processes, CpuMode, CpuTracker};
+use crate::process_thread::{collect_threads, ThreadInfo};
+use crate::types::{ProcError, ProcessInfo};
+use std::collections::{HashMap, HashSet};
+use std::time::SystemTime;
+
+/// Every process at one point in time, as returned by
+/// [`ProcessMonitor::refresh`].
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub processes: Vec<ProcessInfo>,
+    /// Threads of the processes chosen with
+    /// [`ProcessMonitor::set_thread_pids`], by PID.
+    pub threads: HashMap<i32, Vec<ThreadInfo>>,
+    /// When the sample was taken.
+    pub taken_at: SystemTime,
+}
+
+/// Samples the process list, keeping what it needs to measure CPU usage and
+/// I/O rates from one refresh to the next.
+///
+/// Each monitor measures against its own previous refresh, so a UI and an
+/// exporter in the same process can refresh at different rates without
+/// disturbing each other.
+pub struct ProcessMonitor {
+    tracker: CpuTracker,
+    io_tracker: IoTracker,
+    cpu_mode: CpuMode,
+    detailed_memory: bool,
+    detailed_scheduling: bool,
+    /// Processes whose threads are listed, each with its own tracker so their
+    /// CPU usage is measured like that of processes.
+    thread_trackers: HashMap<i32, CpuTracker>,
+}
+
+impl ProcessMonitor {
+    pub fn new() -> Self {
+        Self {
+            tracker: CpuTracker::new(),
+            io_tracker: IoTracker::new(),
+            cpu_mode: CpuMode::default(),
+            detailed_memory: false,
+            detailed_scheduling: false,
+            thread_trackers: HashMap::new(),
+        }
+    }
+
+    /// How [`ProcessInfo::cpu_percent`] is expressed.
+    pub fn cpu_mode(&self) -> CpuMode {
+        self.cpu_mode
+    }
+
+    /// Choose how later refreshes express CPU usage.
+    pub fn set_cpu_mode(&mut self, mode: CpuMode) {
+        self.cpu_mode = mode;
+    }
+
+    /// Whether refreshes read [`ProcessInfo::memory_details`].
+    pub fn detailed_memory(&self) -> bool {
+        self.detailed_memory
+    }
+
+    /// Also read PSS, USS, shared and swap use of every process on later
+    /// refreshes. Off by default, as it makes a refresh several times slower.
+    pub fn set_detailed_memory(&mut self, enabled: bool) {
+        self.detailed_memory = enabled;
+    }
+
+    /// Whether refreshes read [`ProcessInfo::cpu_affinity`] and
+    /// [`ProcessInfo::io_priority`].
+    pub fn detailed_scheduling(&self) -> bool {
+        self.detailed_scheduling
+    }
+
+    /// Also read the CPU affinity and I/O priority of every process on later
+    /// refreshes. Off by default, as each costs a system call per process.
+    pub fn set_detailed_scheduling(&mut self, enabled: bool) {
+        self.detailed_scheduling = enabled;
+    }
+
+    /// The processes whose threads refreshes list.
+    pub fn thread_pids(&self) -> HashSet<i32> {
+        self.thread_trackers.keys().copied().collect()
+    }
+
+    /// List the threads of these processes in [`Snapshot::threads`] on later
+    /// refreshes. Thread CPU usage reads 0% on the first refresh after a
+    /// process is added.
+    pub fn set_thread_pids(&mut self, pids: impl IntoIterator<Item = i32>) {
+        let pids: HashSet<i32> = pids.into_iter().collect();
+        self.thread_trackers.retain(|pid, _| pids.contains(pid));
+        for pid in pids {
+            self.thread_trackers
+                .entry(pid)
+                .or_insert_with(CpuTracker::new);
+        }
+    }
+
+    /// Sample every process. CPU usage and I/O rates are measured since the
+    /// previous refresh, so they read 0 on the first one.
+    pub fn refresh(&mut self) -> Result<Snapshot, ProcError> {
+        let taken_at = SystemTime::now();
+        let processes = collect_processes(
+            &mut self.tracker,
+            &mut self.io_tracker,
+            self.cpu_mode,
+            self.detailed_memory,
+            self.detailed_scheduling,
+        )?;
+
+        // A process that exited simply has no threads listed
+        let threads = self
+            .thread_trackers
+            .iter_mut()
+            .filter_map(|(&pid, tracker)| {
+                let threads = collect_threads(pid, tracker, self.cpu_mode).ok()?;
+                Some((pid, threads))
+            })
+            .collect();
+
+        Ok(Snapshot {
+            processes,
+            threads,
+            taken_at,
+        })
+    }
+
+    /// Forget every earlier sample, as if the monitor was just created.
+    pub fn reset(&mut self) {
+        self.tracker = CpuTracker::new();
+        self.io_tracker = IoTracker::new();
+        for tracker in self.thread_trackers.values_mut() {
+            *tracker = CpuTracker::new();
+        }
+    }
+}
+
+impl Default for ProcessMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,56 @@
+//! Reading and adjusting how likely the OOM killer is to pick a process.
+
+use crate::safety::{self, Safety};
+use crate::types::ProcError;
+use std::fs;
+use std::io;
+
+/// Valid values for `oom_score_adj`. -1000 exempts a process from the OOM
+/// killer entirely, 1000 makes it the first to go.
+pub const OOM_SCORE_ADJ_RANGE: std::ops::RangeInclusive<i32> = -1000..=1000;
+
+/// The badness score the OOM killer currently gives `pid`, from 0 to 2000.
+/// When memory runs out, the process with the highest score is killed first.
+pub fn oom_score(pid: i32) -> Result<u32, ProcError> {
+    read_value(pid, "oom_score")
+}
+
+/// The adjustment added to `pid`'s OOM score, in [`OOM_SCORE_ADJ_RANGE`].
+pub fn oom_score_adj(pid: i32) -> Result<i32, ProcError> {
+    read_value(pid, "oom_score_adj")
+}
+
+/// Write `pid`'s `oom_score_adj`. Use a negative value to protect a critical
+/// service and a positive one to offer up a sacrificial worker.
+///
+/// Lowering the value below what it was when the process started needs
+/// `CAP_SYS_RESOURCE` and otherwise fails with [`ProcError::PermissionDenied`].
+pub fn set_oom_score_adj(pid: i32, adj: i32, safety: Safety) -> Result<(), ProcError> {
+    if !OOM_SCORE_ADJ_RANGE.contains(&adj) {
+        return Err(ProcError::OomError(
+            pid,
+            format!("oom_score_adj {} is outside -1000..=1000", adj),
+        ));
+    }
+    safety::check(pid, safety)?;
+
+    let path = format!("/proc/{}/oom_score_adj", pid);
+    fs::write(&path, adj.to_string()).map_err(|e| io_error(pid, &path, e))
+}
+
+fn read_value<T: std::str::FromStr>(pid: i32, file: &str) -> Result<T, ProcError> {
+    let path = format!("/proc/{}/{}", pid, file);
+    let content = fs::read_to_string(&path).map_err(|e| io_error(pid, &path, e))?;
+    content
+        .trim()
+        .parse()
+        .map_err(|_| ProcError::ProcfsError(format!("Unexpected contents in {}", path)))
+}
+
+fn io_error(pid: i32, path: &str, err: io::Error) -> ProcError {
+    match err.kind() {
+        io::ErrorKind::NotFound => ProcError::NotFound(pid),
+        io::ErrorKind::PermissionDenied => ProcError::PermissionDenied(pid),
+        _ => ProcError::OomError(pid, format!("{}: {}", path, err)),
+    }
+}
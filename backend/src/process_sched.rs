@@ -0,0 +1,434 @@
+//! Reading and changing nice values, scheduling policies, CPU affinity and
+//! I/O priorities.
+
+use crate::safety::{self, Safety};
+use crate::types::ProcError;
+use nix::errno::Errno;
+use std::fmt;
+use std::mem;
+
+/// Valid nice values, from most to least favourable.
+pub const NICE_RANGE: std::ops::RangeInclusive<i32> = -20..=19;
+
+/// Valid priorities for the realtime policies.
+pub const RT_PRIORITY_RANGE: std::ops::RangeInclusive<i32> = 1..=99;
+
+/// A Linux scheduling policy. The realtime policies carry their priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchedPolicy {
+    /// `SCHED_OTHER`, the default time-sharing policy.
+    #[default]
+    Other,
+    /// `SCHED_BATCH`, for CPU-bound work that should not preempt interactive tasks.
+    Batch,
+    /// `SCHED_IDLE`, runs only when nothing else wants the CPU.
+    Idle,
+    /// `SCHED_FIFO` with a priority in [`RT_PRIORITY_RANGE`].
+    Fifo(i32),
+    /// `SCHED_RR` with a priority in [`RT_PRIORITY_RANGE`].
+    RoundRobin(i32),
+}
+
+impl SchedPolicy {
+    /// Build a policy from the raw values found in `/proc/<pid>/stat`.
+    pub(crate) fn from_raw(policy: u32, rt_priority: u32) -> Self {
+        match policy as libc::c_int {
+            libc::SCHED_BATCH => SchedPolicy::Batch,
+            libc::SCHED_IDLE => SchedPolicy::Idle,
+            libc::SCHED_FIFO => SchedPolicy::Fifo(rt_priority as i32),
+            libc::SCHED_RR => SchedPolicy::RoundRobin(rt_priority as i32),
+            _ => SchedPolicy::Other,
+        }
+    }
+
+    fn raw(self) -> (libc::c_int, libc::c_int) {
+        match self {
+            SchedPolicy::Other => (libc::SCHED_OTHER, 0),
+            SchedPolicy::Batch => (libc::SCHED_BATCH, 0),
+            SchedPolicy::Idle => (libc::SCHED_IDLE, 0),
+            SchedPolicy::Fifo(priority) => (libc::SCHED_FIFO, priority),
+            SchedPolicy::RoundRobin(priority) => (libc::SCHED_RR, priority),
+        }
+    }
+
+    /// Whether this is one of the realtime policies.
+    pub fn is_realtime(self) -> bool {
+        matches!(self, SchedPolicy::Fifo(_) | SchedPolicy::RoundRobin(_))
+    }
+}
+
+impl fmt::Display for SchedPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedPolicy::Other => f.write_str("SCHED_OTHER"),
+            SchedPolicy::Batch => f.write_str("SCHED_BATCH"),
+            SchedPolicy::Idle => f.write_str("SCHED_IDLE"),
+            SchedPolicy::Fifo(priority) => write!(f, "SCHED_FIFO ({})", priority),
+            SchedPolicy::RoundRobin(priority) => write!(f, "SCHED_RR ({})", priority),
+        }
+    }
+}
+
+/// Valid levels within the realtime and best-effort I/O classes, 0 being the
+/// most favourable.
+pub const IO_LEVEL_RANGE: std::ops::RangeInclusive<i32> = 0..=7;
+
+/// `IOPRIO_WHO_PROCESS` from the kernel's `ioprio.h`; despite its name it
+/// targets a single thread.
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+
+/// The I/O class is stored above the level in an ioprio value.
+const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+
+/// An I/O scheduling class, as set by `ionice`. The realtime and best-effort
+/// classes carry a level.
+///
+/// Variants are ordered from most to least favourable, so sorting by this
+/// puts the most demanding processes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum IoPriority {
+    /// Always served first, with a level in [`IO_LEVEL_RANGE`].
+    Realtime(i32),
+    /// Served in turn, with a level in [`IO_LEVEL_RANGE`].
+    BestEffort(i32),
+    /// No class set: best-effort with a level derived from the nice value.
+    #[default]
+    None,
+    /// Only served when no other process needs the disk.
+    Idle,
+}
+
+impl IoPriority {
+    fn from_raw(raw: libc::c_int) -> Self {
+        let level = raw & ((1 << IOPRIO_CLASS_SHIFT) - 1);
+        match raw >> IOPRIO_CLASS_SHIFT {
+            1 => IoPriority::Realtime(level),
+            2 => IoPriority::BestEffort(level),
+            3 => IoPriority::Idle,
+            _ => IoPriority::None,
+        }
+    }
+
+    fn raw(self) -> libc::c_int {
+        let (class, level) = match self {
+            IoPriority::None => (0, 0),
+            IoPriority::Realtime(level) => (1, level),
+            IoPriority::BestEffort(level) => (2, level),
+            IoPriority::Idle => (3, 0),
+        };
+        (class << IOPRIO_CLASS_SHIFT) | level
+    }
+}
+
+impl fmt::Display for IoPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IoPriority::Realtime(level) => write!(f, "realtime {}", level),
+            IoPriority::BestEffort(level) => write!(f, "best-effort {}", level),
+            IoPriority::None => f.write_str("none"),
+            IoPriority::Idle => f.write_str("idle"),
+        }
+    }
+}
+
+/// Which threads of a process a scheduling change applies to.
+///
+/// Linux schedules threads, not processes: the nice value, policy and
+/// affinity of the main thread are inherited by threads created later, but
+/// threads that already exist keep their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThreadScope {
+    /// Only the main thread, whose TID is the PID.
+    #[default]
+    MainThread,
+    /// Every thread currently in the process.
+    AllThreads,
+    /// A single thread of the process, by TID. Fails with
+    /// [`ProcError::NotFound`] for the TID if the thread is not, or no
+    /// longer, part of the process.
+    Thread(i32),
+}
+
+/// Number of CPUs configured on the system, i.e. the size of the affinity grid.
+pub fn cpu_count() -> usize {
+    // SAFETY: sysconf has no preconditions
+    let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_CONF) };
+    count.max(1) as usize
+}
+
+/// The nice value of `pid`'s main thread.
+pub fn nice(pid: i32) -> Result<i32, ProcError> {
+    // getpriority can legitimately return -1, so errors are told apart by errno
+    Errno::clear();
+    // SAFETY: getpriority only reads its integer arguments
+    let value = unsafe { libc::getpriority(libc::PRIO_PROCESS, pid as libc::id_t) };
+    match Errno::last_raw() {
+        0 => Ok(value),
+        raw => Err(sched_error(pid, Errno::from_raw(raw))),
+    }
+}
+
+/// The scheduling policy of `pid`'s main thread.
+pub fn sched_policy(pid: i32) -> Result<SchedPolicy, ProcError> {
+    // SAFETY: sched_getscheduler only reads its integer argument
+    let policy = unsafe { libc::sched_getscheduler(pid) };
+    if policy < 0 {
+        return Err(sched_error(pid, Errno::last()));
+    }
+
+    // SAFETY: an all-zero sched_param is valid
+    let mut param: libc::sched_param = unsafe { mem::zeroed() };
+    // SAFETY: param is a valid, writable sched_param
+    if unsafe { libc::sched_getparam(pid, &mut param) } < 0 {
+        return Err(sched_error(pid, Errno::last()));
+    }
+
+    // SCHED_RESET_ON_FORK may be or'ed into the policy
+    let policy = policy & !libc::SCHED_RESET_ON_FORK;
+    Ok(SchedPolicy::from_raw(
+        policy as u32,
+        param.sched_priority as u32,
+    ))
+}
+
+/// The CPUs `pid`'s main thread may run on, as indices from 0.
+pub fn cpu_affinity(pid: i32) -> Result<Vec<usize>, ProcError> {
+    // SAFETY: an all-zero cpu_set_t is a valid, empty set
+    let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+    // SAFETY: set is a valid, writable cpu_set_t of the size passed
+    let ret = unsafe { libc::sched_getaffinity(pid, mem::size_of::<libc::cpu_set_t>(), &mut set) };
+    if ret < 0 {
+        return Err(sched_error(pid, Errno::last()));
+    }
+
+    let max = (mem::size_of::<libc::cpu_set_t>() * 8).min(cpu_count());
+    // SAFETY: CPU_ISSET only reads the set, and every index is within its size
+    Ok((0..max)
+        .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+        .collect())
+}
+
+/// The I/O priority of `pid`'s main thread.
+pub fn io_priority(pid: i32) -> Result<IoPriority, ProcError> {
+    // SAFETY: ioprio_get takes two integers and returns the priority or -1
+    let ret = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, pid) };
+    if ret < 0 {
+        return Err(sched_error(pid, Errno::last()));
+    }
+    Ok(IoPriority::from_raw(ret as libc::c_int))
+}
+
+/// Set the I/O priority of `pid`. The realtime class needs `CAP_SYS_ADMIN`.
+///
+/// Only I/O schedulers that support priorities (BFQ, and CFQ on older
+/// kernels) act on it; with `mq-deadline` or `none` it has no effect.
+pub fn set_io_priority(
+    pid: i32,
+    priority: IoPriority,
+    scope: ThreadScope,
+    safety: Safety,
+) -> Result<(), ProcError> {
+    if let IoPriority::Realtime(level) | IoPriority::BestEffort(level) = priority {
+        if !IO_LEVEL_RANGE.contains(&level) {
+            return Err(ProcError::SchedError(
+                pid,
+                format!("I/O level {} is outside 0..=7", level),
+            ));
+        }
+    }
+    safety::check(pid, safety)?;
+
+    let raw = priority.raw();
+    for_each_thread(pid, scope, |tid| {
+        // SAFETY: ioprio_set takes three integers and returns 0 or -1
+        let ret = unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid, raw) };
+        if ret < 0 {
+            Err(Errno::last())
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// Set the nice value of `pid`, from -20 (most CPU) to 19 (least).
+///
+/// Raising the priority (lowering the nice value) below the current limit
+/// needs `CAP_SYS_NICE` and otherwise fails with
+/// [`ProcError::PermissionDenied`].
+pub fn set_nice(pid: i32, nice: i32, scope: ThreadScope, safety: Safety) -> Result<(), ProcError> {
+    if !NICE_RANGE.contains(&nice) {
+        return Err(ProcError::SchedError(
+            pid,
+            format!("nice value {} is outside -20..=19", nice),
+        ));
+    }
+    safety::check(pid, safety)?;
+
+    for_each_thread(pid, scope, |tid| {
+        // SAFETY: setpriority only reads its integer arguments
+        let ret = unsafe { libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice) };
+        if ret < 0 {
+            Err(Errno::last())
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// Set the scheduling policy of `pid`. Switching to a realtime policy needs
+/// `CAP_SYS_NICE` or a suitable `RLIMIT_RTPRIO`.
+pub fn set_sched_policy(
+    pid: i32,
+    policy: SchedPolicy,
+    scope: ThreadScope,
+    safety: Safety,
+) -> Result<(), ProcError> {
+    let (raw_policy, priority) = policy.raw();
+    if policy.is_realtime() && !RT_PRIORITY_RANGE.contains(&priority) {
+        return Err(ProcError::SchedError(
+            pid,
+            format!("realtime priority {} is outside 1..=99", priority),
+        ));
+    }
+    safety::check(pid, safety)?;
+
+    let param = libc::sched_param {
+        sched_priority: priority,
+    };
+    for_each_thread(pid, scope, |tid| {
+        // SAFETY: param is a valid sched_param that outlives the call
+        if unsafe { libc::sched_setscheduler(tid, raw_policy, &param) } < 0 {
+            Err(Errno::last())
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// Restrict `pid` to the given CPUs, as indices from 0.
+pub fn set_cpu_affinity(
+    pid: i32,
+    cpus: &[usize],
+    scope: ThreadScope,
+    safety: Safety,
+) -> Result<(), ProcError> {
+    let max = mem::size_of::<libc::cpu_set_t>() * 8;
+    if cpus.is_empty() {
+        return Err(ProcError::SchedError(
+            pid,
+            "at least one CPU must be allowed".to_string(),
+        ));
+    }
+    if let Some(&cpu) = cpus.iter().find(|&&cpu| cpu >= max) {
+        return Err(ProcError::SchedError(
+            pid,
+            format!("CPU {} is out of range", cpu),
+        ));
+    }
+    safety::check(pid, safety)?;
+
+    // SAFETY: an all-zero cpu_set_t is a valid, empty set
+    let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+    for &cpu in cpus {
+        // SAFETY: cpu was checked to be within the set
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    for_each_thread(pid, scope, |tid| {
+        // SAFETY: set is a valid cpu_set_t of the size passed
+        let ret = unsafe { libc::sched_setaffinity(tid, mem::size_of::<libc::cpu_set_t>(), &set) };
+        if ret < 0 {
+            Err(Errno::last())
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// Run `apply` on the threads of `pid` that `scope` selects.
+fn for_each_thread(
+    pid: i32,
+    scope: ThreadScope,
+    mut apply: impl FnMut(i32) -> Result<(), Errno>,
+) -> Result<(), ProcError> {
+    match scope {
+        ThreadScope::MainThread => return apply(pid).map_err(|e| sched_error(pid, e)),
+        ThreadScope::Thread(tid) => {
+            // The safety check was on `pid`, so skip a TID that is not one of its threads
+            if !is_thread_of(pid, tid) {
+                return Err(ProcError::NotFound(tid));
+            }
+            return apply(tid).map_err(|e| match e {
+                Errno::ESRCH => ProcError::NotFound(tid),
+                e => sched_error(pid, e),
+            });
+        }
+        ThreadScope::AllThreads => {}
+    }
+
+    let tasks = procfs::process::Process::new(pid)
+        .and_then(|proc| proc.tasks())
+        .map_err(|_| ProcError::NotFound(pid))?;
+    for task in tasks.flatten() {
+        match apply(task.tid) {
+            // The thread exited while we were going through the list
+            Ok(()) | Err(Errno::ESRCH) => {}
+            Err(e) => return Err(sched_error(pid, e)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `tid` is a live thread of `pid`, probed with a null signal
+/// through tgkill, which fails with ESRCH for a thread of another process.
+fn is_thread_of(pid: i32, tid: i32) -> bool {
+    // SAFETY: tgkill takes three integers and returns 0 or -1; signal 0 only
+    // checks that the thread exists
+    let ret = unsafe { libc::syscall(libc::SYS_tgkill, pid, tid, 0) };
+    // EPERM still means the thread exists, the change itself will then fail
+    ret == 0 || Errno::last() == Errno::EPERM
+}
+
+/// Map the errno of a scheduling call on `pid` to the matching variant.
+fn sched_error(pid: i32, errno: Errno) -> ProcError {
+    match errno {
+        Errno::EPERM | Errno::EACCES => ProcError::PermissionDenied(pid),
+        Errno::ESRCH => ProcError::NotFound(pid),
+        e => ProcError::SchedError(pid, e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thread_scope_refuses_a_tid_of_another_process() {
+        let pid = std::process::id() as i32;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+        let worker = std::thread::spawn(move || {
+            // SAFETY: gettid has no preconditions
+            tx.send(unsafe { libc::gettid() }).unwrap();
+            done_rx.recv().unwrap();
+        });
+        let tid = rx.recv().unwrap();
+
+        let mut applied = Vec::new();
+        let result = for_each_thread(pid, ThreadScope::Thread(tid), |tid| {
+            applied.push(tid);
+            Ok(())
+        });
+        assert!(result.is_ok());
+        // PID 1 is never one of our threads, so nothing may be applied to it
+        let result = for_each_thread(pid, ThreadScope::Thread(1), |tid| {
+            applied.push(tid);
+            Ok(())
+        });
+        assert!(matches!(result, Err(ProcError::NotFound(1))));
+        assert_eq!(applied, vec![tid]);
+
+        done_tx.send(()).unwrap();
+        worker.join().unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+//! Sending arbitrary signals, including realtime ones.
+
+use crate::process_kill::open_handle;
+use crate::safety::{self, Safety};
+use crate::types::ProcError;
+use nix::errno::Errno;
+use nix::sys::signal::Signal;
+use std::fmt;
+
+/// A signal that can be sent with [`send_signal`]: one of the standard
+/// signals, or a realtime signal given as an offset from `SIGRTMIN`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignalKind {
+    Standard(Signal),
+    Realtime(i32),
+}
+
+impl SignalKind {
+    /// Number of realtime signals available, so `Realtime(n)` is valid for
+    /// `0..realtime_count()`.
+    pub fn realtime_count() -> i32 {
+        libc::SIGRTMAX() - libc::SIGRTMIN() + 1
+    }
+
+    /// The raw signal number, or `None` for an out-of-range realtime offset.
+    pub fn number(self) -> Option<i32> {
+        match self {
+            SignalKind::Standard(signal) => Some(signal as i32),
+            SignalKind::Realtime(offset) => (0..Self::realtime_count())
+                .contains(&offset)
+                .then(|| libc::SIGRTMIN() + offset),
+        }
+    }
+}
+
+impl From<Signal> for SignalKind {
+    fn from(signal: Signal) -> Self {
+        SignalKind::Standard(signal)
+    }
+}
+
+impl fmt::Display for SignalKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignalKind::Standard(signal) => f.write_str(signal.as_str()),
+            SignalKind::Realtime(0) => f.write_str("SIGRTMIN"),
+            SignalKind::Realtime(offset) => write!(f, "SIGRTMIN+{}", offset),
+        }
+    }
+}
+
+/// Send a single signal to a process, through a pidfd when supported.
+///
+/// Unlike the kill functions this does nothing else: no escalation and no
+/// waiting. Useful for SIGHUP reloads, SIGUSR1/SIGUSR2 log rotation and the
+/// like. Accepts a plain [`Signal`] or a [`SignalKind`].
+pub fn send_signal(
+    pid: i32,
+    signal: impl Into<SignalKind>,
+    safety: Safety,
+) -> Result<(), ProcError> {
+    safety::check(pid, safety)?;
+
+    let signal = signal.into();
+    let number = signal
+        .number()
+        .ok_or_else(|| ProcError::SignalError(pid, format!("{} is out of range", signal)))?;
+
+    open_handle(pid)?
+        .signal_number(number)
+        .map_err(|e| ProcError::from_errno(pid, e))
+}
+
+/// Send a single signal to one thread of a process, with `tgkill`.
+///
+/// Only signals the program handles are delivered to that thread: the
+/// default action of a signal, like stopping or terminating, still applies
+/// to the whole process.
+pub fn send_thread_signal(
+    pid: i32,
+    tid: i32,
+    signal: impl Into<SignalKind>,
+    safety: Safety,
+) -> Result<(), ProcError> {
+    safety::check(pid, safety)?;
+
+    let signal = signal.into();
+    let number = signal
+        .number()
+        .ok_or_else(|| ProcError::SignalError(pid, format!("{} is out of range", signal)))?;
+
+    // tgkill fails with ESRCH unless the thread belongs to `pid`, so a TID
+    // reused by another process is never signalled
+    // SAFETY: tgkill takes three integers and returns 0 or -1
+    let ret = unsafe { libc::syscall(libc::SYS_tgkill, pid, tid, number) };
+    if ret < 0 {
+        return Err(ProcError::from_errno(pid, Errno::last()));
+    }
+    Ok(())
+}
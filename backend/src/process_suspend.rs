@@ -0,0 +1,90 @@
+//! Pausing and resuming processes, process trees and cgroups.
+
+use crate::cgroup;
+use crate::pidfd::ProcessHandle;
+use crate::process_kill::{open_handle, stop_tree};
+use crate::process_list::read_children_map;
+use crate::safety::{self, Safety};
+use crate::types::ProcError;
+use nix::sys::signal::Signal;
+
+/// Pause a process with SIGSTOP. It keeps its memory but gets no CPU time
+/// until it is resumed.
+pub fn suspend_pid(pid: i32, safety: Safety) -> Result<(), ProcError> {
+    safety::check(pid, safety)?;
+    open_handle(pid)?
+        .signal(Signal::SIGSTOP)
+        .map_err(|e| ProcError::from_errno(pid, e))
+}
+
+/// Resume a process paused with SIGSTOP (or SIGTSTP, SIGTTIN, ...).
+///
+/// Letting a process run again cannot hurt it, so this is never refused by
+/// the safety layer.
+pub fn resume_pid(pid: i32) -> Result<(), ProcError> {
+    open_handle(pid)?
+        .signal(Signal::SIGCONT)
+        .map_err(|e| ProcError::from_errno(pid, e))
+}
+
+/// Pause a process and all its descendants with SIGSTOP.
+///
+/// The tree is rescanned until every member is stopped, so children forked
+/// along the way are caught too. Returns the PIDs that were stopped.
+///
+/// Unless `safety` overrides it, nothing is touched if any process in the tree
+/// is protected.
+pub fn suspend_tree(pid: i32, safety: Safety) -> Result<Vec<i32>, ProcError> {
+    let levels = stop_tree(pid, safety)?;
+    Ok(levels.iter().flatten().map(ProcessHandle::pid).collect())
+}
+
+/// Resume a process and all its descendants with SIGCONT, parents first.
+/// Returns the PIDs that were resumed.
+pub fn resume_tree(pid: i32) -> Result<Vec<i32>, ProcError> {
+    let root = open_handle(pid)?;
+    let children = read_children_map()?;
+
+    let mut resumed = Vec::new();
+    let mut pending = vec![root];
+    let mut index = 0;
+    while index < pending.len() {
+        let parent = pending[index].pid();
+        for &child in children.get(&parent).into_iter().flatten() {
+            // A child that already exited has nothing to resume
+            if let Ok(handle) = ProcessHandle::open(child) {
+                pending.push(handle);
+            }
+        }
+        index += 1;
+    }
+
+    for handle in pending {
+        if handle.signal(Signal::SIGCONT).is_ok() {
+            resumed.push(handle.pid());
+        }
+    }
+
+    Ok(resumed)
+}
+
+/// Freeze every process in a cgroup v2 through `cgroup.freeze`.
+///
+/// Unlike SIGSTOP, freezing cannot be noticed or undone by the processes
+/// themselves or by their parents, and it also covers child cgroups.
+///
+/// Unless `safety` overrides it, nothing is touched if any task in the cgroup
+/// is protected.
+pub fn suspend_cgroup(cgroup_path: &str, safety: Safety) -> Result<(), ProcError> {
+    let dir = cgroup::resolve(cgroup_path)?;
+    for pid in cgroup::procs_recursive(&dir)? {
+        safety::check(pid, safety)?;
+    }
+    cgroup::set_frozen(&dir, true)
+}
+
+/// Thaw a cgroup v2 frozen with [`suspend_cgroup`].
+pub fn resume_cgroup(cgroup_path: &str) -> Result<(), ProcError> {
+    let dir = cgroup::resolve(cgroup_path)?;
+    cgroup::set_frozen(&dir, false)
+}
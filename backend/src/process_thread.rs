@@ -0,0 +1,77 @@
+//! Listing the threads of a process from `/proc/<pid>/task`, each with its
+//! own CPU usage.
+
+use crate::process_list::{CpuMode, CpuTracker};
+use crate::process_sched::{self, SchedPolicy};
+use crate::types::ProcError;
+
+/// A thread of a process.
+#[derive(Debug, Clone)]
+pub struct ThreadInfo {
+    /// Thread ID; the main thread's is the PID.
+    pub tid: i32,
+    /// Thread name (`comm`), as set with `pthread_setname_np`, truncated by
+    /// the kernel to 15 bytes.
+    pub name: String,
+    pub state: String,
+    /// CPU usage since the previous listing of the process's threads, per
+    /// [`CpuMode`](crate::CpuMode). A thread runs on one core at a time, so
+    /// it stays within 100% in either mode.
+    pub cpu_percent: f32,
+    /// CPU the thread last ran on.
+    pub last_cpu: Option<usize>,
+    pub nice: i32,
+    /// Kernel scheduling priority, as in [`ProcessInfo::priority`](crate::ProcessInfo::priority).
+    pub priority: i32,
+    pub sched_policy: SchedPolicy,
+    /// CPUs the thread may run on, as indices from 0.
+    pub cpu_affinity: Vec<usize>,
+}
+
+/// List the threads of `pid`, measuring CPU usage against the previous
+/// listing made with `tracker`.
+pub(crate) fn collect_threads(
+    pid: i32,
+    tracker: &mut CpuTracker,
+    mode: CpuMode,
+) -> Result<Vec<ThreadInfo>, ProcError> {
+    let tasks = procfs::process::Process::new(pid)
+        .and_then(|proc| proc.tasks())
+        .map_err(|_| ProcError::NotFound(pid))?;
+
+    tracker.begin_listing();
+
+    let mut threads = Vec::new();
+    let mut current_tids = Vec::new();
+    // A thread may exit while we go through the list
+    for task in tasks.flatten() {
+        let Ok(stat) = task.stat() else {
+            continue;
+        };
+        current_tids.push(task.tid);
+
+        threads.push(ThreadInfo {
+            tid: task.tid,
+            name: stat.comm.clone(),
+            state: stat.state.to_string(),
+            cpu_percent: tracker.calculate_cpu_percent(
+                task.tid,
+                stat.starttime,
+                stat.utime + stat.stime,
+                mode,
+            ),
+            last_cpu: stat.processor.and_then(|cpu| usize::try_from(cpu).ok()),
+            nice: stat.nice as i32,
+            priority: stat.priority as i32,
+            sched_policy: SchedPolicy::from_raw(
+                stat.policy.unwrap_or(0),
+                stat.rt_priority.unwrap_or(0),
+            ),
+            cpu_affinity: process_sched::cpu_affinity(task.tid).unwrap_or_default(),
+        });
+    }
+
+    tracker.cleanup_old_processes(&current_tids);
+
+    Ok(threads)
+}
@@ -0,0 +1,241 @@
+//! Finding zombies and orphaned process trees, and getting zombies reaped.
+
+use crate::kill_policy::KillPolicy;
+use crate::process_kill::kill_pid;
+use crate::process_signal::send_signal;
+use crate::safety::Safety;
+use crate::types::{KillOutcome, ProcError};
+use nix::sys::signal::Signal;
+use std::collections::{HashMap, HashSet};
+
+/// Names of processes that commonly make themselves child subreapers, so
+/// orphans below them are adopted by them rather than by init.
+const SUBREAPERS: &[&str] = &[
+    "systemd",
+    "tini",
+    "dumb-init",
+    "catatonit",
+    "conmon",
+    "containerd-shim",
+];
+
+/// A parent process that has exited children it has not reaped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZombieParent {
+    pub pid: i32,
+    pub name: String,
+    /// PIDs of the zombie children.
+    pub zombies: Vec<i32>,
+}
+
+/// A process tree that was reparented to init or to a subreaper when its
+/// parent exited, e.g. a build left running after its terminal closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrphanedTree {
+    /// Topmost orphaned process.
+    pub root: i32,
+    pub name: String,
+    /// The process it was reparented to.
+    pub reaper: i32,
+    pub reaper_name: String,
+    /// Number of processes in the tree, the root included.
+    pub size: usize,
+}
+
+/// Result of [`analyze_zombies`].
+#[derive(Debug, Clone, Default)]
+pub struct ZombieReport {
+    /// Parents with zombie children, the most zombies first.
+    pub parents: Vec<ZombieParent>,
+    pub orphans: Vec<OrphanedTree>,
+}
+
+impl ZombieReport {
+    /// Total number of zombies, across all parents.
+    pub fn zombie_count(&self) -> usize {
+        self.parents.iter().map(|parent| parent.zombies.len()).sum()
+    }
+}
+
+/// What is needed about each process to analyse the tree.
+struct Entry {
+    name: String,
+    ppid: i32,
+    pgid: i32,
+    sid: i32,
+    zombie: bool,
+}
+
+/// Group zombies by the parent that fails to reap them, and find orphaned
+/// process trees.
+///
+/// A process is the root of an orphaned tree when its parent is init or a
+/// known subreaper, which is who the kernel hands it to when its parent
+/// exits. Session leaders and daemons are not reported, as services and
+/// double-forked daemons live under init by design.
+pub fn analyze_zombies() -> Result<ZombieReport, ProcError> {
+    let all_procs = procfs::process::all_processes()
+        .map_err(|e| ProcError::Other(format!("Failed to read /proc: {}", e)))?;
+
+    let mut entries: HashMap<i32, Entry> = HashMap::new();
+    for proc in all_procs.flatten() {
+        if let Ok(stat) = proc.stat() {
+            entries.insert(
+                stat.pid,
+                Entry {
+                    zombie: stat.state == 'Z',
+                    name: stat.comm,
+                    ppid: stat.ppid,
+                    pgid: stat.pgrp,
+                    sid: stat.session,
+                },
+            );
+        }
+    }
+
+    let mut zombies_by_parent: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (&pid, entry) in &entries {
+        if entry.zombie {
+            zombies_by_parent.entry(entry.ppid).or_default().push(pid);
+        }
+    }
+    let mut parents: Vec<ZombieParent> = zombies_by_parent
+        .into_iter()
+        .map(|(pid, mut zombies)| {
+            zombies.sort_unstable();
+            ZombieParent {
+                pid,
+                name: entries
+                    .get(&pid)
+                    .map_or_else(|| "?".to_string(), |entry| entry.name.clone()),
+                zombies,
+            }
+        })
+        .collect();
+    parents.sort_by(|a, b| {
+        b.zombies
+            .len()
+            .cmp(&a.zombies.len())
+            .then(a.pid.cmp(&b.pid))
+    });
+
+    Ok(ZombieReport {
+        parents,
+        orphans: find_orphans(&entries),
+    })
+}
+
+fn find_orphans(entries: &HashMap<i32, Entry>) -> Vec<OrphanedTree> {
+    let is_reaper = |pid: i32| {
+        pid == 1
+            || entries
+                .get(&pid)
+                .is_some_and(|entry| SUBREAPERS.contains(&entry.name.as_str()))
+    };
+    // A daemon double-forks out of a session it created, leaving it in that
+    // session's first process group; a shell puts each job in a group of its own
+    let is_daemon = |entry: &Entry| entry.pgid == entry.sid;
+    let is_orphan_root = |pid: i32, entry: &Entry| {
+        !entry.zombie && is_reaper(entry.ppid) && entry.sid != pid && !is_daemon(entry)
+    };
+
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (&pid, entry) in entries {
+        children.entry(entry.ppid).or_default().push(pid);
+    }
+
+    let mut orphans: Vec<OrphanedTree> = entries
+        .iter()
+        .filter(|&(&pid, entry)| is_orphan_root(pid, entry))
+        .map(|(&pid, entry)| {
+            let reaper = entries.get(&entry.ppid);
+            OrphanedTree {
+                root: pid,
+                name: entry.name.clone(),
+                reaper: entry.ppid,
+                reaper_name: reaper.map_or_else(|| "?".to_string(), |r| r.name.clone()),
+                size: subtree_size(pid, &children),
+            }
+        })
+        .collect();
+    orphans.sort_by(|a, b| b.size.cmp(&a.size).then(a.root.cmp(&b.root)));
+    orphans
+}
+
+fn subtree_size(root: i32, children: &HashMap<i32, Vec<i32>>) -> usize {
+    let mut seen = HashSet::from([root]);
+    let mut pending = vec![root];
+    while let Some(pid) = pending.pop() {
+        for &child in children.get(&pid).into_iter().flatten() {
+            if seen.insert(child) {
+                pending.push(child);
+            }
+        }
+    }
+    seen.len()
+}
+
+/// Send SIGCHLD to a parent with zombie children, prompting a parent that
+/// merely missed the signal to reap them.
+pub fn nudge_zombie_parent(parent: i32, safety: Safety) -> Result<(), ProcError> {
+    send_signal(parent, Signal::SIGCHLD, safety)
+}
+
+/// Kill a parent that does not reap its zombie children, so they are
+/// reparented to init (or a subreaper), which reaps them.
+pub fn kill_zombie_parent(
+    parent: i32,
+    policy: &KillPolicy,
+    safety: Safety,
+) -> Result<KillOutcome, ProcError> {
+    kill_pid(parent, policy, safety)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, ppid: i32, pgid: i32, sid: i32) -> Entry {
+        Entry {
+            name: name.to_string(),
+            ppid,
+            pgid,
+            sid,
+            zombie: false,
+        }
+    }
+
+    #[test]
+    fn find_orphans_classifies_adopted_processes() {
+        let mut entries = HashMap::from([
+            (1, entry("systemd", 0, 1, 1)),
+            // Service started by init, leading its own session
+            (400, entry("sshd", 1, 400, 400)),
+            // User manager, a subreaper
+            (500, entry("systemd", 1, 500, 500)),
+            // Double-forked daemon whose session leader exited
+            (600, entry("daemon", 1, 599, 599)),
+            // Job left behind by a closed terminal, with a child of its own
+            (700, entry("make", 1, 700, 650)),
+            (701, entry("cc1", 700, 700, 650)),
+            // Job adopted by the user manager
+            (800, entry("rsync", 500, 800, 750)),
+            // Still under its shell
+            (900, entry("bash", 1, 900, 900)),
+            (901, entry("vim", 900, 901, 900)),
+            // Adopted by something that is not a subreaper
+            (1000, entry("worker", 901, 1000, 950)),
+        ]);
+        let mut zombie = entry("defunct", 1, 1100, 1050);
+        zombie.zombie = true;
+        entries.insert(1100, zombie);
+
+        let orphans = find_orphans(&entries);
+        let found: Vec<(i32, i32, usize)> = orphans
+            .iter()
+            .map(|tree| (tree.root, tree.reaper, tree.size))
+            .collect();
+        assert_eq!(found, vec![(700, 1, 2), (800, 500, 1)]);
+        assert_eq!(orphans[1].reaper_name, "systemd");
+    }
+}
@@ -0,0 +1,147 @@
+//! Safeguards that keep destructive actions away from critical processes.
+
+use crate::types::ProcError;
+use nix::unistd::{getpid, getsid};
+use std::fmt;
+use std::sync::RwLock;
+
+/// `PF_KTHREAD` from the kernel's `sched.h`: set in the stat flags of kernel threads.
+const PF_KTHREAD: u32 = 0x0020_0000;
+
+/// Process names treated as critical when no custom list has been set.
+const DEFAULT_CRITICAL_NAMES: &[&str] = &[
+    "systemd",
+    "systemd-logind",
+    "systemd-journal",
+    "dbus-daemon",
+    "dbus-broker",
+    "Xorg",
+    "Xwayland",
+    "gnome-shell",
+    "kwin_wayland",
+    "kwin_x11",
+    "plasmashell",
+    "sway",
+    "gdm",
+    "sddm",
+    "lightdm",
+    "sshd",
+];
+
+/// Custom critical names; `None` means [`DEFAULT_CRITICAL_NAMES`].
+static CRITICAL_NAMES: RwLock<Option<Vec<String>>> = RwLock::new(None);
+
+/// Why a process is protected from being killed or signalled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtectionReason {
+    /// PID 1.
+    Init,
+    /// A kernel thread, including kthreadd itself.
+    KernelThread,
+    /// The process calling into this library.
+    OwnProcess,
+    /// A parent, grandparent, ... of the calling process.
+    Ancestor,
+    /// The leader of the calling process's session, usually the login shell.
+    SessionLeader,
+    /// Its name is on the critical names list.
+    CriticalName(String),
+}
+
+impl fmt::Display for ProtectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtectionReason::Init => f.write_str("it is init (PID 1)"),
+            ProtectionReason::KernelThread => f.write_str("it is a kernel thread"),
+            ProtectionReason::OwnProcess => f.write_str("it is the calling process itself"),
+            ProtectionReason::Ancestor => f.write_str("it is an ancestor of the calling process"),
+            ProtectionReason::SessionLeader => {
+                f.write_str("it is the leader of the current login session")
+            }
+            ProtectionReason::CriticalName(name) => write!(f, "{} is a critical process", name),
+        }
+    }
+}
+
+/// Whether an action may target protected processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Safety {
+    /// Refuse with [`ProcError::Protected`].
+    #[default]
+    Enforce,
+    /// The user explicitly confirmed acting on a protected process.
+    Override,
+}
+
+/// Replace the list of process names treated as critical.
+pub fn set_critical_names(names: Vec<String>) {
+    *CRITICAL_NAMES.write().unwrap() = Some(names);
+}
+
+/// The process names currently treated as critical.
+pub fn critical_names() -> Vec<String> {
+    match &*CRITICAL_NAMES.read().unwrap() {
+        Some(names) => names.clone(),
+        None => DEFAULT_CRITICAL_NAMES
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+    }
+}
+
+/// Classify `pid`, returning why it is protected or `None` if it is fair game.
+pub fn protection_reason(pid: i32) -> Option<ProtectionReason> {
+    if pid == 1 {
+        return Some(ProtectionReason::Init);
+    }
+
+    let own_pid = getpid().as_raw();
+    if pid == own_pid {
+        return Some(ProtectionReason::OwnProcess);
+    }
+    if getsid(None).map(|sid| sid.as_raw() == pid).unwrap_or(false) {
+        return Some(ProtectionReason::SessionLeader);
+    }
+    if is_ancestor_of(pid, own_pid) {
+        return Some(ProtectionReason::Ancestor);
+    }
+
+    let stat = procfs::process::Process::new(pid)
+        .and_then(|proc| proc.stat())
+        .ok()?;
+    if pid == 2 || stat.ppid == 2 || stat.flags & PF_KTHREAD != 0 {
+        return Some(ProtectionReason::KernelThread);
+    }
+    if critical_names().contains(&stat.comm) {
+        return Some(ProtectionReason::CriticalName(stat.comm));
+    }
+
+    None
+}
+
+/// Fail with [`ProcError::Protected`] if `pid` is protected and `safety` does
+/// not override it.
+pub(crate) fn check(pid: i32, safety: Safety) -> Result<(), ProcError> {
+    if safety == Safety::Override {
+        return Ok(());
+    }
+    match protection_reason(pid) {
+        Some(reason) => Err(ProcError::Protected(pid, reason)),
+        None => Ok(()),
+    }
+}
+
+/// Walk up the parent chain of `descendant` looking for `pid`.
+fn is_ancestor_of(pid: i32, descendant: i32) -> bool {
+    let mut current = descendant;
+    while current > 1 {
+        let Ok(stat) = procfs::process::Process::new(current).and_then(|proc| proc.stat()) else {
+            return false;
+        };
+        if stat.ppid == pid {
+            return true;
+        }
+        current = stat.ppid;
+    }
+    false
+}
@@ -0,0 +1,294 @@
+//! Sampling the process list on a background thread, so a UI never waits
+//! on `/proc` and gets new data at a steady interval.
+
+use crate::process_diff::SnapshotDiff;
+use crate::process_list::CpuMode;
+use crate::process_monitor::{ProcessMonitor, Snapshot};
+use crate::types::ProcError;
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// One sample published by a [`Sampler`].
+#[derive(Debug)]
+pub struct Sample {
+    pub snapshot: Snapshot,
+    /// Changes since the previous sample; `None` for the first one.
+    pub diff: Option<SnapshotDiff>,
+}
+
+/// Settings and mailbox shared with the sampling thread.
+struct State {
+    interval: Duration,
+    paused: bool,
+    cpu_mode: CpuMode,
+    detailed_memory: bool,
+    detailed_scheduling: bool,
+    thread_pids: HashSet<i32>,
+    /// Take a sample right away, even while paused.
+    refresh_now: bool,
+    stop: bool,
+    latest: Option<Arc<Sample>>,
+    subscribers: Vec<Sender<Arc<Sample>>>,
+    notify: Option<Arc<dyn Fn() + Send + Sync>>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    wake: Condvar,
+}
+
+/// Refreshes the process list on its own thread every `interval` and
+/// publishes each [`Sample`] to its subscribers.
+///
+/// The first sample is taken as soon as the sampler starts. The thread stops
+/// when the sampler is dropped.
+pub struct Sampler {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Sampler {
+    /// Shortest time between samples; shorter intervals are raised to it so
+    /// the thread never spins on `/proc`.
+    pub const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Start sampling every `interval` on a new thread, failing only if the
+    /// thread cannot be spawned.
+    pub fn start(interval: Duration) -> Result<Self, ProcError> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                interval: interval.max(Self::MIN_INTERVAL),
+                paused: false,
+                cpu_mode: CpuMode::default(),
+                detailed_memory: false,
+                detailed_scheduling: false,
+                thread_pids: HashSet::new(),
+                refresh_now: true,
+                stop: false,
+                latest: None,
+                subscribers: Vec::new(),
+                notify: None,
+            }),
+            wake: Condvar::new(),
+        });
+
+        let thread = {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name("process-sampler".to_string())
+                .spawn(move || run(&shared))
+                .map_err(|e| {
+                    ProcError::Other(format!("Failed to spawn the sampler thread: {}", e))
+                })?
+        };
+
+        Ok(Self {
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    /// Receive every sample published from now on. Read it with `try_recv` or
+    /// `try_iter` to never block; dropping the receiver unsubscribes.
+    pub fn subscribe(&self) -> Receiver<Arc<Sample>> {
+        let (sender, receiver) = mpsc::channel();
+        self.shared.state.lock().unwrap().subscribers.push(sender);
+        receiver
+    }
+
+    /// The most recent sample, if any was taken yet.
+    pub fn latest(&self) -> Option<Arc<Sample>> {
+        self.shared.state.lock().unwrap().latest.clone()
+    }
+
+    /// Call `notify` on the sampling thread after each sample is published,
+    /// e.g. to wake up a UI.
+    pub fn set_notify(&self, notify: impl Fn() + Send + Sync + 'static) {
+        self.shared.state.lock().unwrap().notify = Some(Arc::new(notify));
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.shared.state.lock().unwrap().interval
+    }
+
+    /// Change the time between samples, counted from the last one. Raised to
+    /// [`Sampler::MIN_INTERVAL`] if shorter.
+    pub fn set_interval(&self, interval: Duration) {
+        let interval = interval.max(Self::MIN_INTERVAL);
+        self.update(|state| {
+            let changed = state.interval != interval;
+            state.interval = interval;
+            changed
+        });
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.shared.state.lock().unwrap().paused
+    }
+
+    /// Stop or restart sampling at the interval. [`Sampler::refresh_now`]
+    /// still works while paused.
+    pub fn set_paused(&self, paused: bool) {
+        self.update(|state| {
+            let changed = state.paused != paused;
+            state.paused = paused;
+            changed
+        });
+    }
+
+    /// How [`ProcessInfo::cpu_percent`](crate::ProcessInfo::cpu_percent) is
+    /// expressed from the next sample on.
+    pub fn set_cpu_mode(&self, mode: CpuMode) {
+        self.update(|state| {
+            let changed = state.cpu_mode != mode;
+            state.cpu_mode = mode;
+            changed
+        });
+    }
+
+    /// Read [`ProcessInfo::memory_details`](crate::ProcessInfo::memory_details)
+    /// from the next sample on.
+    pub fn set_detailed_memory(&self, enabled: bool) {
+        self.update(|state| {
+            let changed = state.detailed_memory != enabled;
+            state.detailed_memory = enabled;
+            changed
+        });
+    }
+
+    /// Read [`ProcessInfo::cpu_affinity`](crate::ProcessInfo::cpu_affinity)
+    /// and [`ProcessInfo::io_priority`](crate::ProcessInfo::io_priority) from
+    /// the next sample on.
+    pub fn set_detailed_scheduling(&self, enabled: bool) {
+        self.update(|state| {
+            let changed = state.detailed_scheduling != enabled;
+            state.detailed_scheduling = enabled;
+            changed
+        });
+    }
+
+    /// The processes whose threads are listed.
+    pub fn thread_pids(&self) -> HashSet<i32> {
+        self.shared.state.lock().unwrap().thread_pids.clone()
+    }
+
+    /// List the threads of these processes in
+    /// [`Snapshot::threads`](crate::Snapshot::threads) from the next sample on.
+    pub fn set_thread_pids(&self, pids: impl IntoIterator<Item = i32>) {
+        let pids: HashSet<i32> = pids.into_iter().collect();
+        self.update(|state| {
+            let changed = state.thread_pids != pids;
+            state.thread_pids = pids;
+            changed
+        });
+    }
+
+    /// Take a sample right away instead of waiting for the interval.
+    pub fn refresh_now(&self) {
+        self.update(|state| {
+            state.refresh_now = true;
+            true
+        });
+    }
+
+    /// Apply `change` and wake the thread if it reports a change.
+    fn update(&self, change: impl FnOnce(&mut State) -> bool) {
+        if change(&mut self.shared.state.lock().unwrap()) {
+            self.shared.wake.notify_one();
+        }
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        self.update(|state| {
+            state.stop = true;
+            true
+        });
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run(shared: &Shared) {
+    let mut monitor = ProcessMonitor::new();
+    let mut last_sample = Instant::now();
+
+    loop {
+        let (cpu_mode, detailed_memory, detailed_scheduling, thread_pids) = {
+            let mut state = shared.state.lock().unwrap();
+            loop {
+                if state.stop {
+                    return;
+                }
+                if state.refresh_now {
+                    state.refresh_now = false;
+                    break;
+                }
+                if state.paused {
+                    state = shared.wake.wait(state).unwrap();
+                    continue;
+                }
+                let due = last_sample + state.interval;
+                let now = Instant::now();
+                if now >= due {
+                    break;
+                }
+                state = shared.wake.wait_timeout(state, due - now).unwrap().0;
+            }
+            (
+                state.cpu_mode,
+                state.detailed_memory,
+                state.detailed_scheduling,
+                state.thread_pids.clone(),
+            )
+        };
+
+        monitor.set_cpu_mode(cpu_mode);
+        monitor.set_detailed_memory(detailed_memory);
+        monitor.set_detailed_scheduling(detailed_scheduling);
+        monitor.set_thread_pids(thread_pids);
+        last_sample = Instant::now();
+        // A failed refresh is simply retried at the next interval
+        let Ok(snapshot) = monitor.refresh() else {
+            continue;
+        };
+
+        let previous = shared.state.lock().unwrap().latest.clone();
+        let diff = previous.map(|previous| SnapshotDiff::between(&previous.snapshot, &snapshot));
+        let sample = Arc::new(Sample { snapshot, diff });
+
+        let notify = {
+            let mut state = shared.state.lock().unwrap();
+            state.latest = Some(sample.clone());
+            state
+                .subscribers
+                .retain(|subscriber| subscriber.send(sample.clone()).is_ok());
+            state.notify.clone()
+        };
+        // Outside the lock, so `notify` may call back into the sampler
+        if let Some(notify) = notify {
+            notify();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interval_is_never_below_the_minimum() {
+        let sampler = Sampler::start(Duration::ZERO).unwrap();
+        assert_eq!(sampler.interval(), Sampler::MIN_INTERVAL);
+
+        sampler.set_interval(Duration::from_secs(2));
+        assert_eq!(sampler.interval(), Duration::from_secs(2));
+        sampler.set_interval(Duration::ZERO);
+        assert_eq!(sampler.interval(), Sampler::MIN_INTERVAL);
+    }
+}
@@ -0,0 +1,249 @@
+//! A trash bin for processes: stop them now, kill or restore them later.
+
+use crate::cgroup;
+use crate::kill_policy::KillPolicy;
+use crate::pidfd::ProcessHandle;
+use crate::process_kill::{self, escalate, kill_levels, open_handle, stop_tree};
+use crate::process_list::resident_bytes;
+use crate::safety::{self, Safety};
+use crate::types::{PidOutcome, ProcError, ProcessInfo};
+use nix::sys::signal::Signal;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// What was moved to the trash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrashTarget {
+    /// A single process, stopped with SIGSTOP.
+    Process(i32),
+    /// A process and its descendants, all stopped with SIGSTOP.
+    Tree { root: i32, count: usize },
+    /// A cgroup v2, frozen through `cgroup.freeze`.
+    Cgroup(String),
+}
+
+/// Handles on the stopped processes, so restoring or killing them later can
+/// never hit a recycled PID.
+enum Members {
+    Process(ProcessHandle),
+    Tree(Vec<Vec<ProcessHandle>>),
+    Cgroup(PathBuf),
+}
+
+/// A stopped process, tree or cgroup waiting in the trash.
+pub struct TrashItem {
+    pub id: u64,
+    pub target: TrashTarget,
+    pub name: String,
+    pub trashed_at: SystemTime,
+    /// CPU usage just before the item was stopped.
+    pub cpu_percent: f32,
+    /// Memory held by the item, which stays allocated while it sits in the trash.
+    pub memory_bytes: u64,
+    members: Members,
+}
+
+impl TrashItem {
+    /// Stop `process` with SIGSTOP, ready to be moved to a [`Trash`].
+    ///
+    /// Stopping a tree or freezing a cgroup can take a while, so this lets it
+    /// happen without holding on to the trash.
+    pub fn stop_process(process: &ProcessInfo, safety: Safety) -> Result<Self, ProcError> {
+        safety::check(process.pid, safety)?;
+        let handle = open_handle(process.pid)?;
+        handle
+            .signal(Signal::SIGSTOP)
+            .map_err(|e| ProcError::from_errno(process.pid, e))?;
+
+        Ok(Self::new(
+            TrashTarget::Process(process.pid),
+            process.name.clone(),
+            process.cpu_percent,
+            process.memory_bytes,
+            Members::Process(handle),
+        ))
+    }
+
+    /// Stop `process` and all its descendants, ready to be moved to a
+    /// [`Trash`] as one item.
+    pub fn stop_tree(process: &ProcessInfo, safety: Safety) -> Result<Self, ProcError> {
+        let levels = stop_tree(process.pid, safety)?;
+        let members: Vec<i32> = levels.iter().flatten().map(|h| h.pid()).collect();
+        let memory_bytes = members.iter().map(|&pid| resident_bytes(pid)).sum();
+
+        Ok(Self::new(
+            TrashTarget::Tree {
+                root: process.pid,
+                count: members.len(),
+            },
+            process.name.clone(),
+            process.cpu_percent,
+            memory_bytes,
+            Members::Tree(levels),
+        ))
+    }
+
+    /// Freeze a cgroup v2, ready to be moved to a [`Trash`].
+    pub fn freeze_cgroup(cgroup_path: &str, safety: Safety) -> Result<Self, ProcError> {
+        let dir = cgroup::resolve(cgroup_path)?;
+        for pid in cgroup::procs_recursive(&dir)? {
+            safety::check(pid, safety)?;
+        }
+        cgroup::set_frozen(&dir, true)?;
+
+        let memory_bytes = cgroup::memory_current(&dir).unwrap_or(0);
+        Ok(Self::new(
+            TrashTarget::Cgroup(cgroup_path.to_string()),
+            cgroup_path.to_string(),
+            0.0,
+            memory_bytes,
+            Members::Cgroup(dir),
+        ))
+    }
+
+    /// The id is assigned when the item is moved to a [`Trash`].
+    fn new(
+        target: TrashTarget,
+        name: String,
+        cpu_percent: f32,
+        memory_bytes: u64,
+        members: Members,
+    ) -> Self {
+        Self {
+            id: 0,
+            target,
+            name,
+            trashed_at: SystemTime::now(),
+            cpu_percent,
+            memory_bytes,
+            members,
+        }
+    }
+
+    /// How long the item has been in the trash.
+    pub fn age(&self) -> Duration {
+        self.trashed_at.elapsed().unwrap_or_default()
+    }
+
+    /// Let the item run again with SIGCONT, or by thawing its cgroup.
+    pub fn restore(self) -> Result<(), ProcError> {
+        match self.members {
+            Members::Process(handle) => handle
+                .signal(Signal::SIGCONT)
+                .map_err(|e| ProcError::from_errno(handle.pid(), e)),
+            Members::Tree(levels) => {
+                // Parents first, so nothing resumes before whoever waits on it
+                for handle in levels.iter().flatten() {
+                    let _ = handle.signal(Signal::SIGCONT);
+                }
+                Ok(())
+            }
+            Members::Cgroup(dir) => cgroup::set_frozen(&dir, false),
+        }
+    }
+
+    /// Kill the item with `policy`. It was checked against the safety layer
+    /// when it was trashed, so this does not check again.
+    pub fn kill(self, policy: &KillPolicy) -> Result<Vec<PidOutcome>, ProcError> {
+        match self.members {
+            Members::Process(handle) => Ok(escalate(vec![handle], policy, |_| Ok(false))),
+            Members::Tree(levels) => Ok(kill_levels(levels, policy)),
+            Members::Cgroup(dir) => {
+                // kill_cgroup notices the cgroup is frozen and thaws it when done
+                process_kill::kill_cgroup(&dir.to_string_lossy(), policy, Safety::Override)
+            }
+        }
+    }
+}
+
+/// Holds trashed items until they are restored, emptied, or expire.
+///
+/// Items still in the trash when it is dropped, e.g. when the app exits, are
+/// restored rather than left stopped for good.
+pub struct Trash {
+    items: Vec<TrashItem>,
+    next_id: u64,
+    retention: Duration,
+}
+
+impl Trash {
+    /// Create an empty trash whose items expire after `retention`.
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            items: Vec::new(),
+            next_id: 1,
+            retention,
+        }
+    }
+
+    pub fn items(&self) -> &[TrashItem] {
+        &self.items
+    }
+
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    pub fn set_retention(&mut self, retention: Duration) {
+        self.retention = retention;
+    }
+
+    /// Stop `process` with SIGSTOP and move it to the trash.
+    pub fn trash_process(
+        &mut self,
+        process: &ProcessInfo,
+        safety: Safety,
+    ) -> Result<u64, ProcError> {
+        Ok(self.insert(TrashItem::stop_process(process, safety)?))
+    }
+
+    /// Stop `process` and all its descendants and move them to the trash as
+    /// one item.
+    pub fn trash_tree(&mut self, process: &ProcessInfo, safety: Safety) -> Result<u64, ProcError> {
+        Ok(self.insert(TrashItem::stop_tree(process, safety)?))
+    }
+
+    /// Freeze a cgroup v2 and move it to the trash.
+    pub fn trash_cgroup(&mut self, cgroup_path: &str, safety: Safety) -> Result<u64, ProcError> {
+        Ok(self.insert(TrashItem::freeze_cgroup(cgroup_path, safety)?))
+    }
+
+    /// Move an item stopped with one of the [`TrashItem`] constructors to the
+    /// trash, returning its id.
+    pub fn insert(&mut self, mut item: TrashItem) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        item.id = id;
+        self.items.push(item);
+        id
+    }
+
+    /// Take an item out of the trash, to restore or kill it.
+    pub fn take(&mut self, id: u64) -> Option<TrashItem> {
+        let index = self.items.iter().position(|item| item.id == id)?;
+        Some(self.items.remove(index))
+    }
+
+    /// Take every item out of the trash.
+    pub fn take_all(&mut self) -> Vec<TrashItem> {
+        std::mem::take(&mut self.items)
+    }
+
+    /// Take out the items that have been in the trash longer than the
+    /// retention period. The caller is expected to kill them.
+    pub fn take_expired(&mut self) -> Vec<TrashItem> {
+        let (expired, kept) = std::mem::take(&mut self.items)
+            .into_iter()
+            .partition(|item| item.age() >= self.retention);
+        self.items = kept;
+        expired
+    }
+}
+
+impl Drop for Trash {
+    fn drop(&mut self) {
+        for item in self.take_all() {
+            let _ = item.restore();
+        }
+    }
+}
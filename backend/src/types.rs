@@ -1,16 +1,160 @@
 //! Data types and error definitions for process management.
 
+use crate::process_io::{IoRates, IoStats};
+use crate::process_memory::MemoryDetails;
+use crate::process_sched::{IoPriority, SchedPolicy};
+use crate::safety::ProtectionReason;
+use nix::errno::Errno;
+use nix::sys::signal::Signal;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 use thiserror::Error;
 
 /// Represents a process entry.
 #[derive(Debug, Clone)]
 pub struct ProcessInfo {
     pub pid: i32,
+    /// Command name (`comm`), truncated by the kernel to 15 bytes.
     pub name: String,
+    /// Full command line, the program first. Empty for kernel threads and
+    /// zombies.
+    pub cmdline: Vec<String>,
+    /// Path of the executable, if it can be read (usually only for our own
+    /// user's processes).
+    pub exe: Option<PathBuf>,
+    /// Current working directory, if it can be read.
+    pub cwd: Option<PathBuf>,
+    /// Real user ID, and the name it resolves to (the UID itself if unknown).
+    pub uid: u32,
+    pub user: String,
+    /// Effective user ID, which differs from `uid` for setuid programs.
+    pub euid: u32,
+    pub effective_user: String,
+    /// When the process was started.
+    pub start_time: SystemTime,
+    /// How long the process has been running, as of the listing.
+    pub age: Duration,
+    /// Number of threads.
+    pub threads: u32,
+    /// CPU usage since the previous listing, per [`CpuMode`](crate::CpuMode).
     pub cpu_percent: f32,
+    /// Resident set size, counting shared pages in full.
     pub memory_bytes: u64,
+    /// PSS, USS, shared and swap use, only read when detailed memory
+    /// accounting is enabled and `None` if it was not or could not be read.
+    pub memory_details: Option<MemoryDetails>,
+    /// I/O done since the process started, `None` if it could not be read.
+    pub io: Option<IoStats>,
+    /// I/O per second since the previous listing, `None` if it could not be
+    /// read.
+    pub io_rates: Option<IoRates>,
     pub state: String,
     pub ppid: i32,
+    /// Process group ID, shared by the processes of a shell pipeline or job.
+    pub pgid: i32,
+    /// Session ID, shared by everything started from one terminal or login.
+    pub sid: i32,
+    /// How the process is paused, or `None` if it is not.
+    pub suspended: Option<Suspension>,
+    /// Nice value of the main thread, from -20 to 19.
+    pub nice: i32,
+    /// Kernel scheduling priority of the main thread: 20 + nice for normal
+    /// policies, -2 to -100 for realtime ones.
+    pub priority: i32,
+    /// Scheduling policy of the main thread.
+    pub sched_policy: SchedPolicy,
+    /// CPUs the main thread may run on, as indices from 0. Only read when
+    /// detailed scheduling is enabled, and `None` if it was not or could not
+    /// be read; [`cpu_affinity`](crate::cpu_affinity) reads it on demand.
+    pub cpu_affinity: Option<Vec<usize>>,
+    /// I/O priority of the main thread, read like `cpu_affinity`;
+    /// [`io_priority`](crate::io_priority) reads it on demand.
+    pub io_priority: Option<IoPriority>,
+    /// Badness score from 0 to 2000; the OOM killer picks the highest first.
+    pub oom_score: u32,
+    /// Adjustment from -1000 (never kill) to 1000 (kill first) added to the score.
+    pub oom_score_adj: i32,
+}
+
+/// How a paused process was paused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suspension {
+    /// Stopped by SIGSTOP or a similar signal.
+    Stopped,
+    /// Frozen along with its cgroup through `cgroup.freeze`.
+    Frozen,
+}
+
+/// What happened to a process that was killed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillOutcome {
+    /// The signal after which the process exited. `None` if it had already
+    /// exited before anything was sent, or if it outlived every step.
+    pub effective_signal: Option<Signal>,
+    /// Time from the first signal until the process was seen to exit.
+    pub elapsed: Duration,
+    /// The process had already exited before it was signalled.
+    pub already_dead: bool,
+    /// The process exited but is a zombie waiting for its parent to reap it.
+    pub zombie: bool,
+}
+
+impl KillOutcome {
+    /// Whether the process is known to have exited.
+    pub fn exited(&self) -> bool {
+        self.already_dead || self.effective_signal.is_some()
+    }
+}
+
+/// Per-PID result of an operation that targets several processes.
+#[derive(Debug)]
+pub struct PidOutcome {
+    pub pid: i32,
+    pub result: Result<KillOutcome, ProcError>,
+}
+
+/// Why a process is still alive after SIGKILL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnkillableReason {
+    /// A thread is in uninterruptible sleep (D state) inside the kernel, usually
+    /// waiting on I/O. `wchan` is the kernel function it is blocked in, if known.
+    DiskSleep { tid: i32, wchan: Option<String> },
+    /// The process has exited but `parent` has not reaped it yet.
+    Zombie { parent: i32 },
+    /// The process is being traced by a debugger, which holds it stopped.
+    Traced { tracer: i32 },
+    /// None of the above; `state` is the last state seen in `/proc/<pid>/stat`.
+    Unknown { state: char },
+}
+
+impl fmt::Display for UnkillableReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnkillableReason::DiskSleep {
+                tid,
+                wchan: Some(wchan),
+            } => write!(
+                f,
+                "thread {} is in uninterruptible sleep (D) in {}",
+                tid, wchan
+            ),
+            UnkillableReason::DiskSleep { tid, wchan: None } => {
+                write!(f, "thread {} is in uninterruptible sleep (D)", tid)
+            }
+            UnkillableReason::Zombie { parent } => write!(
+                f,
+                "it is a zombie and its parent {} is not reaping it",
+                parent
+            ),
+            UnkillableReason::Traced { tracer } => {
+                write!(f, "it is being traced by debugger PID {}", tracer)
+            }
+            UnkillableReason::Unknown { state } => {
+                write!(f, "no known cause (state {})", state)
+            }
+        }
+    }
 }
 
 /// Errors that can occur during process management.
@@ -18,12 +162,20 @@ pub struct ProcessInfo {
 pub enum ProcError {
     #[error("Permission denied for PID {0}")]
     PermissionDenied(i32),
-    #[error("Process {0} is in unkillable state (D or zombie)")]
-    UnkillableState(i32),
+    #[error("Process {0} survived SIGKILL: {1}")]
+    UnkillableState(i32, UnkillableReason),
     #[error("Process {0} not found")]
     NotFound(i32),
     #[error("Failed to send signal to PID {0}: {1}")]
     SignalError(i32, String),
+    #[error("Process {0} is protected because {1}")]
+    Protected(i32, ProtectionReason),
+    #[error("Failed to change scheduling of PID {0}: {1}")]
+    SchedError(i32, String),
+    #[error("Failed to change resource limits of PID {0}: {1}")]
+    LimitError(i32, String),
+    #[error("Failed to access the OOM score of PID {0}: {1}")]
+    OomError(i32, String),
     #[error("Cgroup operation failed: {0}")]
     CgroupError(String),
     #[error("Other error: {0}")]
@@ -32,6 +184,17 @@ pub enum ProcError {
     ProcfsError(String),
 }
 
+impl ProcError {
+    /// Map the errno from signalling `pid` to the matching variant.
+    pub(crate) fn from_errno(pid: i32, errno: Errno) -> Self {
+        match errno {
+            Errno::EPERM => ProcError::PermissionDenied(pid),
+            Errno::ESRCH => ProcError::NotFound(pid),
+            e => ProcError::SignalError(pid, e.to_string()),
+        }
+    }
+}
+
 impl From<procfs::ProcError> for ProcError {
     fn from(err: procfs::ProcError) -> Self {
         ProcError::ProcfsError(err.to_string())
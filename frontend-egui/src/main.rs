@@ -1,8 +1,13 @@
-use backend::{list_processes, ProcessInfo};
+use backend::{ProcError, Sample, Sampler};
 use eframe::{egui, App};
-use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
 use ui::header::Header;
+use ui::kill_matching::KillMatchingView;
 use ui::status_bar::StatusBar;
+use ui::trash_view::TrashView;
+use ui::zombie_view::ZombieView;
 
 mod ui;
 use ui::process_table::ProcessTable;
@@ -27,32 +32,65 @@ async fn main() -> eframe::Result<()> {
             style.spacing.button_padding = egui::vec2(12.0, 8.0);
             cc.egui_ctx.set_style(style);
 
-            Box::new(ProcessManagerApp::default())
+            match ProcessManagerApp::new(&cc.egui_ctx) {
+                Ok(app) => Box::new(app),
+                Err(e) => Box::new(StartupError(e.to_string())),
+            }
         }),
     )
 }
 
 struct ProcessManagerApp {
-    processes: Arc<Mutex<Vec<ProcessInfo>>>,
+    /// Refreshes the process list on a background thread.
+    sampler: Sampler,
+    samples: Receiver<Arc<Sample>>,
+    /// The sample currently shown.
+    sample: Option<Arc<Sample>>,
     process_table: ProcessTable,
     header: Header,
+    kill_matching: KillMatchingView,
+    zombies: ZombieView,
 }
 
-impl Default for ProcessManagerApp {
-    fn default() -> Self {
-        // Load processes once at startup
-        let processes = Arc::new(Mutex::new(Vec::new()));
-        if let Ok(list) = list_processes() {
-            if let Ok(mut proc_lock) = processes.lock() {
-                *proc_lock = list;
-            }
-        }
+impl ProcessManagerApp {
+    fn new(ctx: &egui::Context) -> Result<Self, ProcError> {
+        let header = Header::default();
+        let sampler = Sampler::start(header.refresh_interval())?;
+
+        // Redraw as soon as new data arrives
+        let repaint_ctx = ctx.clone();
+        sampler.set_notify(move || repaint_ctx.request_repaint());
 
-        Self {
-            processes,
+        // Subscribe before reading the latest sample so none is missed
+        let samples = sampler.subscribe();
+        let sample = sampler.latest();
+
+        Ok(Self {
+            sampler,
+            samples,
+            sample,
             process_table: ProcessTable::default(),
-            header: Header::default(),
-        }
+            header,
+            kill_matching: KillMatchingView::default(),
+            zombies: ZombieView::default(),
+        })
+    }
+}
+
+/// Shown instead of the app when it could not start, e.g. when the sampler
+/// thread could not be spawned.
+struct StartupError(String);
+
+impl App for StartupError {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.centered_and_justified(|ui| {
+                ui.colored_label(
+                    egui::Color32::from_rgb(230, 70, 70),
+                    format!("Trash Manager could not start: {}", self.0),
+                );
+            });
+        });
     }
 }
 
@@ -63,30 +101,91 @@ impl App for ProcessManagerApp {
             let (search_changed, refresh_requested) = self.header.show(ui, &mut self.process_table);
             ui.add_space(6.0);
 
-            // Handle refresh request
+            // Pass the header's settings on to the sampler
+            self.sampler.set_cpu_mode(self.header.cpu_mode);
+            self.sampler
+                .set_detailed_memory(self.process_table.memory_metric.is_detailed());
+            self.sampler
+                .set_detailed_scheduling(self.process_table.show_io_priority);
+            self.sampler.set_paused(self.header.paused);
+            self.sampler.set_interval(self.header.refresh_interval());
             if refresh_requested {
-                if let Ok(list) = list_processes() {
-                    if let Ok(mut proc_lock) = self.processes.lock() {
-                        *proc_lock = list;
-                    }
+                self.sampler.refresh_now();
+            }
+
+            // Take in new samples without blocking, flashing what changed
+            for sample in self.samples.try_iter() {
+                if let Some(diff) = &sample.diff {
+                    self.process_table.apply_diff(diff);
                 }
+                self.sample = Some(sample);
             }
+            let sample = self.sample.clone();
+            let processes = sample
+                .as_ref()
+                .map_or(&[][..], |sample| sample.snapshot.processes.as_slice());
+            let no_threads = HashMap::new();
+            let threads = sample
+                .as_ref()
+                .map_or(&no_threads, |sample| &sample.snapshot.threads);
 
-            // Take a snapshot for rendering
-            let processes = {
-                let lock = self.processes.lock().unwrap();
-                lock.clone()
-            };
+            // Highlight the rows a pending "Kill matching" would hit
+            self.process_table.highlighted = self.kill_matching.preview_pids();
 
             // Show process table with search filter
-            let filtered_count = self
-                .process_table
-                .show(ui, &processes, &self.header.search_text);
+            let filtered_count =
+                self.process_table
+                    .show(ui, processes, threads, &self.header.search_text);
+
+            // List the threads of expanded processes, right away when one is
+            // expanded
+            if self.sampler.thread_pids() != self.process_table.expanded {
+                self.sampler
+                    .set_thread_pids(self.process_table.expanded.iter().copied());
+                self.sampler.refresh_now();
+            }
 
             ui.add_space(6.0);
 
             // Show status bar
-            StatusBar::show(ui, &processes, filtered_count);
+            StatusBar::show(
+                ui,
+                processes,
+                filtered_count,
+                self.process_table.memory_metric,
+            );
+
+            // Trash window, and automatic killing of expired trash items
+            let policy = self.process_table.kill_preset.policy();
+            let trash = &self.process_table.trash;
+            let notifications = &self.process_table.notifications;
+            TrashView::empty_expired(ctx, trash, &policy, notifications);
+            TrashView::show(
+                ctx,
+                &mut self.header.show_trash,
+                trash,
+                &policy,
+                notifications,
+            );
+
+            // pkill-style window with a dry-run preview
+            self.kill_matching.show(
+                ctx,
+                &mut self.header.show_kill_matching,
+                &policy,
+                notifications,
+            );
+
+            // Zombies grouped by parent, and orphaned process trees
+            self.zombies
+                .show(ctx, &mut self.header.show_zombies, &policy, notifications);
+
+            // Report results of background actions
+            notifications.show_toasts(ctx);
+            egui::Window::new("Notification log")
+                .open(&mut self.header.show_notification_log)
+                .default_size(egui::vec2(480.0, 320.0))
+                .show(ctx, |ui| notifications.show_log(ui));
 
             // Request repaint if search changed for immediate filtering
             if search_changed {
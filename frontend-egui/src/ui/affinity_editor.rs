@@ -0,0 +1,145 @@
+//! Per-core grid for choosing which CPUs a process or thread may run on.
+
+use backend::{cpu_affinity, cpu_count, ProcessInfo, ThreadInfo};
+use eframe::egui;
+
+/// CPUs per row of the grid.
+const COLUMNS: usize = 8;
+
+pub struct AffinityEditor {
+    pub process: ProcessInfo,
+    /// TID of the single thread being edited, if not the process.
+    pub thread: Option<i32>,
+    current: Vec<usize>,
+    allowed: Vec<bool>,
+}
+
+impl AffinityEditor {
+    pub fn new(process: &ProcessInfo) -> Self {
+        // Listings only include the affinity when asked to
+        let current = process
+            .cpu_affinity
+            .clone()
+            .or_else(|| cpu_affinity(process.pid).ok())
+            .unwrap_or_default();
+        Self::with_current(process, None, &current)
+    }
+
+    /// Edit the affinity of a single thread of `process`.
+    pub fn for_thread(process: &ProcessInfo, thread: &ThreadInfo) -> Self {
+        Self::with_current(process, Some(thread.tid), &thread.cpu_affinity)
+    }
+
+    fn with_current(process: &ProcessInfo, thread: Option<i32>, current: &[usize]) -> Self {
+        let mut allowed = vec![false; cpu_count()];
+        for &cpu in current {
+            if let Some(slot) = allowed.get_mut(cpu) {
+                *slot = true;
+            }
+        }
+
+        Self {
+            process: process.clone(),
+            thread,
+            current: current.to_vec(),
+            allowed,
+        }
+    }
+
+    /// Show the editor window. Returns the chosen CPUs when the user applies
+    /// them; `open` is cleared when the window should go away.
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) -> Option<Vec<usize>> {
+        let mut applied = None;
+        let mut window_open = true;
+
+        let title = match self.thread {
+            Some(tid) => format!(
+                "CPU affinity: {} ({}), thread {}",
+                self.process.name, self.process.pid, tid
+            ),
+            None => format!("CPU affinity: {} ({})", self.process.name, self.process.pid),
+        };
+        egui::Window::new(title)
+            .open(&mut window_open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Currently allowed: {}",
+                    format_cpu_list(&self.current)
+                ));
+                ui.add_space(4.0);
+
+                egui::Grid::new("affinity_grid").show(ui, |ui| {
+                    for (cpu, allowed) in self.allowed.iter_mut().enumerate() {
+                        ui.checkbox(allowed, format!("CPU {}", cpu));
+                        if cpu % COLUMNS == COLUMNS - 1 {
+                            ui.end_row();
+                        }
+                    }
+                });
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("All").clicked() {
+                        self.allowed.fill(true);
+                    }
+                    if ui.button("None").clicked() {
+                        self.allowed.fill(false);
+                    }
+
+                    let cpus = self.selected();
+                    if ui
+                        .add_enabled(!cpus.is_empty(), egui::Button::new("Apply"))
+                        .on_disabled_hover_text("Allow at least one CPU")
+                        .clicked()
+                    {
+                        applied = Some(cpus);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        *open = false;
+                    }
+                });
+            });
+
+        if !window_open || applied.is_some() {
+            *open = false;
+        }
+        applied
+    }
+
+    fn selected(&self) -> Vec<usize> {
+        self.allowed
+            .iter()
+            .enumerate()
+            .filter(|(_, &allowed)| allowed)
+            .map(|(cpu, _)| cpu)
+            .collect()
+    }
+}
+
+/// Format CPU indices compactly, e.g. `0-3,6`.
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == cpu => *end = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+
+    if ranges.is_empty() {
+        return "none".to_string();
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -1,20 +1,44 @@
 //! Header component with title, search, and hamburger menu.
 
+use crate::ui::process_table::MemoryMetric;
+use backend::CpuMode;
 use eframe::egui;
+use std::time::Duration;
 
 pub struct Header {
     pub search_text: String,
+    pub show_notification_log: bool,
+    pub show_trash: bool,
+    pub show_kill_matching: bool,
+    pub show_zombies: bool,
+    /// How the CPU column expresses usage.
+    pub cpu_mode: CpuMode,
+    /// Stop refreshing automatically.
+    pub paused: bool,
+    /// Seconds between automatic refreshes.
+    pub refresh_secs: f32,
 }
 
 impl Default for Header {
     fn default() -> Self {
         Self {
             search_text: String::new(),
+            show_notification_log: false,
+            show_trash: false,
+            show_kill_matching: false,
+            show_zombies: false,
+            cpu_mode: CpuMode::default(),
+            paused: false,
+            refresh_secs: 1.0,
         }
     }
 }
 
 impl Header {
+    pub fn refresh_interval(&self) -> Duration {
+        Duration::from_secs_f32(self.refresh_secs)
+    }
+
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
@@ -50,20 +74,98 @@ impl Header {
             // Refresh button
             ui.add_space(10.0);
             if ui.button("Refresh")
-                .on_hover_text("Click to update process list and CPU usage.\nNote: CPU % shows 0% on first load - wait 1+ seconds between refreshes for accurate values.")
+                .on_hover_text("Update the process list right away.\nNote: CPU % is measured since the previous refresh, so it shows 0% on first load.")
                 .clicked() {
                 refresh_requested = true;
             }
 
+            // Automatic refresh, done in the background
+            let pause_text = if self.paused { "Resume" } else { "Pause" };
+            if ui
+                .selectable_label(self.paused, pause_text)
+                .on_hover_text("Pause or resume automatic refreshing")
+                .clicked()
+            {
+                self.paused = !self.paused;
+            }
+            ui.label("every");
+            ui.add(
+                egui::DragValue::new(&mut self.refresh_secs)
+                    .speed(0.05)
+                    .clamp_range(0.25..=10.0)
+                    .suffix(" s"),
+            );
+
             // Hamburger menu on the right
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let trash_count = process_table.trash.lock().unwrap().items().len();
                 ui.menu_button("Menu", |ui| {
                     ui.set_min_width(120.0);
 
                     ui.label("Show columns:");
                     ui.checkbox(&mut process_table.show_pid, "PID");
                     ui.checkbox(&mut process_table.show_ppid, "PPID");
+                    ui.checkbox(&mut process_table.show_user, "User");
+                    ui.checkbox(&mut process_table.show_threads, "Threads");
+                    ui.checkbox(&mut process_table.show_nice, "Nice");
+                    ui.checkbox(&mut process_table.show_priority, "Priority");
+                    ui.checkbox(&mut process_table.show_age, "Age");
+                    ui.checkbox(&mut process_table.show_io_priority, "I/O priority");
+                    ui.checkbox(&mut process_table.show_disk_read, "Disk read/s");
+                    ui.checkbox(&mut process_table.show_disk_write, "Disk write/s");
+                    ui.checkbox(&mut process_table.show_cmdline, "Command line");
+                    ui.checkbox(&mut process_table.show_exe, "Executable");
+                    ui.checkbox(&mut process_table.show_cwd, "Working directory");
+
+                    ui.separator();
+                    ui.label("CPU %:");
+                    let mode = self.cpu_mode;
+                    ui.radio_value(&mut self.cpu_mode, CpuMode::Irix, "Per core")
+                        .on_hover_text("100% is one full core, so busy threaded processes exceed 100%");
+                    ui.radio_value(&mut self.cpu_mode, CpuMode::Solaris, "Whole machine")
+                        .on_hover_text("100% is every core, so all processes add up to at most 100%");
+                    if self.cpu_mode != mode {
+                        refresh_requested = true;
+                    }
+
+                    ui.separator();
+                    ui.label("Memory:");
+                    let metric = process_table.memory_metric;
+                    for option in MemoryMetric::ALL {
+                        ui.radio_value(&mut process_table.memory_metric, option, option.label())
+                            .on_hover_text(option.description());
+                    }
+                    if process_table.memory_metric != metric {
+                        refresh_requested = true;
+                    }
+
+                    ui.separator();
+                    ui.checkbox(&mut self.show_notification_log, "Notification log");
                 });
+
+                if ui
+                    .selectable_label(self.show_zombies, "Zombies")
+                    .on_hover_text("Parents that do not reap their children, and orphaned trees")
+                    .clicked()
+                {
+                    self.show_zombies = !self.show_zombies;
+                }
+
+                if ui
+                    .selectable_label(self.show_kill_matching, "Kill matching...")
+                    .on_hover_text("Kill every process matching a name, user, terminal, ...")
+                    .clicked()
+                {
+                    self.show_kill_matching = !self.show_kill_matching;
+                }
+
+                if ui
+                    .selectable_label(self.show_trash, format!("Trash ({})", trash_count))
+                    .on_hover_text("Stopped processes waiting to be restored or killed")
+                    .clicked()
+                {
+                    self.show_trash = !self.show_trash;
+                }
             });
         });
 
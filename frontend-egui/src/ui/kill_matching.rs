@@ -0,0 +1,249 @@
+//! pkill-style window: describe which processes to kill, preview the matches,
+//! then kill exactly those.
+
+use crate::ui::notifications::{NotificationKind, NotificationLog};
+use backend::{
+    kill_matched, kill_matching, protection_reason, KillPolicy, MatchCriteria, MatchedProcess,
+    Safety,
+};
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// The form, and the matches of the last preview.
+#[derive(Default)]
+pub struct KillMatchingView {
+    name: String,
+    cmdline: String,
+    user: String,
+    parent: String,
+    tty: String,
+    cgroup: String,
+    min_age_secs: u64,
+    preview: Option<Vec<MatchedProcess>>,
+    /// How many previewed matches are protected, counted once per preview.
+    protected: usize,
+    include_protected: bool,
+}
+
+impl KillMatchingView {
+    /// PIDs of the previewed matches, to highlight in the process table.
+    pub fn preview_pids(&self) -> HashSet<i32> {
+        self.preview
+            .iter()
+            .flatten()
+            .map(|process| process.pid)
+            .collect()
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        policy: &KillPolicy,
+        notifications: &NotificationLog,
+    ) {
+        let mut window_open = *open;
+        egui::Window::new("Kill matching processes")
+            .open(&mut window_open)
+            .default_size(egui::vec2(560.0, 480.0))
+            .show(ctx, |ui| {
+                let edited = self.show_form(ui);
+                // A stale preview would highlight the wrong rows
+                if edited {
+                    self.preview = None;
+                }
+
+                ui.horizontal(|ui| {
+                    let has_criteria = self.has_criteria();
+                    if ui
+                        .add_enabled(has_criteria, egui::Button::new("Preview"))
+                        .on_disabled_hover_text("Set at least one criterion")
+                        .clicked()
+                    {
+                        self.run_preview(policy, notifications);
+                    }
+                    if self.preview.is_some() && ui.button("Clear preview").clicked() {
+                        self.preview = None;
+                    }
+                });
+
+                if self.preview.is_some() {
+                    ui.separator();
+                    self.show_preview(ui, policy, notifications);
+                }
+            });
+
+        // Closing the window drops the highlight along with the preview
+        if !window_open {
+            self.preview = None;
+        }
+        *open = window_open;
+    }
+
+    /// Returns whether any criterion changed.
+    fn show_form(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut edited = false;
+        egui::Grid::new("kill_matching_form")
+            .num_columns(2)
+            .show(ui, |ui| {
+                let fields = [
+                    ("Name (regex)", &mut self.name, "firefox, ^node$, ..."),
+                    ("Command line (regex)", &mut self.cmdline, "--type=renderer"),
+                    ("User", &mut self.user, "name or UID"),
+                    ("Parent PID", &mut self.parent, ""),
+                    ("Terminal", &mut self.tty, "pts/3"),
+                    ("Cgroup", &mut self.cgroup, "/user.slice/..."),
+                ];
+                for (label, value, hint) in fields {
+                    ui.label(label);
+                    edited |= ui
+                        .add(egui::TextEdit::singleline(value).hint_text(hint))
+                        .changed();
+                    ui.end_row();
+                }
+
+                ui.label("Running for at least");
+                edited |= ui
+                    .add(egui::DragValue::new(&mut self.min_age_secs).suffix(" s"))
+                    .changed();
+                ui.end_row();
+            });
+        edited
+    }
+
+    fn criteria(&self) -> Result<MatchCriteria, String> {
+        let text = |value: &String| {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+        let parent = match text(&self.parent) {
+            Some(parent) => Some(
+                parent
+                    .parse()
+                    .map_err(|_| format!("Parent PID {:?} is not a number", parent))?,
+            ),
+            None => None,
+        };
+
+        Ok(MatchCriteria {
+            name: text(&self.name),
+            cmdline: text(&self.cmdline),
+            user: text(&self.user),
+            parent,
+            tty: text(&self.tty),
+            cgroup: text(&self.cgroup),
+            min_age: (self.min_age_secs > 0).then(|| Duration::from_secs(self.min_age_secs)),
+        })
+    }
+
+    /// Whether any criterion is set, so a preview or kill cannot match every
+    /// process. An unparsable one counts, so previewing reports the problem.
+    fn has_criteria(&self) -> bool {
+        self.criteria()
+            .map_or(true, |criteria| !criteria.is_empty())
+    }
+
+    fn run_preview(&mut self, policy: &KillPolicy, notifications: &NotificationLog) {
+        let result = self.criteria().and_then(|criteria| {
+            kill_matching(&criteria, policy, true, Safety::Enforce).map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(report) => {
+                self.protected = report
+                    .matched
+                    .iter()
+                    .filter(|process| protection_reason(process.pid).is_some())
+                    .count();
+                self.preview = Some(report.matched);
+            }
+            Err(e) => notifications.push(NotificationKind::Error, e),
+        }
+        self.include_protected = false;
+    }
+
+    fn show_preview(
+        &mut self,
+        ui: &mut egui::Ui,
+        policy: &KillPolicy,
+        notifications: &NotificationLog,
+    ) {
+        let Some(matched) = self.preview.take() else {
+            return;
+        };
+
+        let protected = self.protected;
+        ui.label(format!(
+            "{} processes match; they are highlighted in the process table.",
+            matched.len()
+        ));
+        if protected > 0 {
+            ui.colored_label(
+                egui::Color32::from_rgb(230, 70, 70),
+                format!("{} of them are protected.", protected),
+            );
+            ui.checkbox(
+                &mut self.include_protected,
+                "Kill protected processes too (may crash your session)",
+            );
+        }
+
+        let can_kill = self.has_criteria()
+            && !matched.is_empty()
+            && (protected == 0 || self.include_protected);
+        let kill_button = egui::Button::new(
+            egui::RichText::new(format!("Kill {} processes", matched.len()))
+                .color(egui::Color32::WHITE),
+        )
+        .fill(egui::Color32::from_rgb(200, 40, 40));
+        if ui.add_enabled(can_kill, kill_button).clicked() {
+            let safety = if self.include_protected {
+                Safety::Override
+            } else {
+                Safety::Enforce
+            };
+            let policy = policy.clone();
+            let notifications = notifications.clone();
+            tokio::task::spawn_blocking(move || {
+                let result = kill_matched(&matched, &policy, safety);
+                notifications.push_group_kill_result("matching processes", &result);
+            });
+            return;
+        }
+
+        ui.add_space(4.0);
+        TableBuilder::new(ui)
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::exact(70.0))
+            .column(Column::exact(140.0))
+            .column(Column::exact(60.0))
+            .column(Column::remainder())
+            .header(22.0, |mut header| {
+                for title in ["PID", "Name", "UID", "Command line"] {
+                    header.col(|ui| {
+                        ui.strong(title);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(22.0, matched.len(), |mut row| {
+                    let process = &matched[row.index()];
+                    row.col(|ui| {
+                        ui.monospace(process.pid.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(&process.name);
+                    });
+                    row.col(|ui| {
+                        ui.label(process.uid.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(&process.cmdline).on_hover_text(&process.cmdline);
+                    });
+                });
+            });
+        self.preview = Some(matched);
+    }
+}
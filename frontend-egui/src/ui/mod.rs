@@ -1,5 +1,11 @@
 //! UI components for the process manager.
 
+pub mod affinity_editor;
 pub mod header;
+pub mod kill_matching;
+pub mod notifications;
+pub mod process_details;
 pub mod process_table;
 pub mod status_bar;
+pub mod trash_view;
+pub mod zombie_view;
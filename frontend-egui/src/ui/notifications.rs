@@ -0,0 +1,230 @@
+//! Toasts and a notification log reporting the results of background actions.
+
+use backend::{KillOutcome, PidOutcome, ProcError};
+use eframe::egui;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a toast stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// Oldest entries are dropped once the log grows past this.
+const MAX_ENTRIES: usize = 200;
+
+/// Survivors listed by PID in a group kill summary before the rest are elided.
+const MAX_LISTED_PIDS: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    Success,
+    Warning,
+    Error,
+}
+
+impl NotificationKind {
+    fn color(self) -> egui::Color32 {
+        match self {
+            NotificationKind::Success => egui::Color32::from_rgb(80, 200, 120),
+            NotificationKind::Warning => egui::Color32::from_rgb(230, 180, 60),
+            NotificationKind::Error => egui::Color32::from_rgb(230, 70, 70),
+        }
+    }
+}
+
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub message: String,
+    pub created: Instant,
+}
+
+/// Shared log that background tasks push into and the UI renders.
+#[derive(Clone, Default)]
+pub struct NotificationLog {
+    entries: Arc<Mutex<Vec<Notification>>>,
+}
+
+impl NotificationLog {
+    pub fn push(&self, kind: NotificationKind, message: impl Into<String>) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.push(Notification {
+            kind,
+            message: message.into(),
+            created: Instant::now(),
+        });
+        if entries.len() > MAX_ENTRIES {
+            let excess = entries.len() - MAX_ENTRIES;
+            entries.drain(..excess);
+        }
+    }
+
+    /// Record the result of killing `name` (`pid`).
+    pub fn push_kill_result(&self, name: &str, pid: i32, result: &Result<KillOutcome, ProcError>) {
+        match result {
+            Ok(outcome) => {
+                let (kind, message) = describe_outcome(name, pid, outcome);
+                self.push(kind, message);
+            }
+            Err(e) => self.push(
+                NotificationKind::Error,
+                format!("Failed to kill {} ({}): {}", name, pid, e),
+            ),
+        }
+    }
+
+    /// Summarize the per-PID results of killing a group of processes called `name`.
+    pub fn push_group_kill_result(&self, name: &str, result: &Result<Vec<PidOutcome>, ProcError>) {
+        let outcomes = match result {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                self.push(
+                    NotificationKind::Error,
+                    format!("Failed to kill {}: {}", name, e),
+                );
+                return;
+            }
+        };
+
+        let killed = outcomes
+            .iter()
+            .filter(|outcome| outcome.result.as_ref().is_ok_and(KillOutcome::exited))
+            .count();
+        // Signalled, but still running once the policy ran out of steps
+        let survivors: Vec<i32> = outcomes
+            .iter()
+            .filter(|outcome| outcome.result.as_ref().is_ok_and(|o| !o.exited()))
+            .map(|outcome| outcome.pid)
+            .collect();
+        let first_failure = outcomes
+            .iter()
+            .find_map(|outcome| outcome.result.as_ref().err());
+
+        if killed == outcomes.len() {
+            self.push(
+                NotificationKind::Success,
+                format!("Killed {} ({} processes)", name, outcomes.len()),
+            );
+            return;
+        }
+
+        let mut message = format!(
+            "Killed {} of {} processes of {}",
+            killed,
+            outcomes.len(),
+            name
+        );
+        if !survivors.is_empty() {
+            let mut pids: Vec<String> = survivors
+                .iter()
+                .take(MAX_LISTED_PIDS)
+                .map(i32::to_string)
+                .collect();
+            if survivors.len() > MAX_LISTED_PIDS {
+                pids.push("...".to_string());
+            }
+            message.push_str(&format!("; still running: {}", pids.join(", ")));
+        }
+        if let Some(failure) = first_failure {
+            message.push_str(&format!("; first failure: {}", failure));
+        }
+        self.push(NotificationKind::Error, message);
+    }
+
+    /// Draw recent notifications as toasts in the bottom-right corner.
+    pub fn show_toasts(&self, ctx: &egui::Context) {
+        let entries = self.entries.lock().unwrap();
+        let recent: Vec<&Notification> = entries
+            .iter()
+            .rev()
+            .take_while(|n| n.created.elapsed() < TOAST_DURATION)
+            .take(5)
+            .collect();
+        if recent.is_empty() {
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -48.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for notification in recent.into_iter().rev() {
+                    egui::Frame::popup(ui.style())
+                        .stroke(egui::Stroke::new(1.0, notification.kind.color()))
+                        .show(ui, |ui| {
+                            ui.set_max_width(360.0);
+                            ui.label(
+                                egui::RichText::new(&notification.message)
+                                    .color(notification.kind.color()),
+                            );
+                        });
+                }
+            });
+
+        // Keep repainting so toasts disappear on time
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+
+    /// Draw the full history, newest first.
+    pub fn show_log(&self, ui: &mut egui::Ui) {
+        let mut entries = self.entries.lock().unwrap();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} entries", entries.len()));
+            if ui.button("Clear").clicked() {
+                entries.clear();
+            }
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for notification in entries.iter().rev() {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{:>4}s ago",
+                            notification.created.elapsed().as_secs()
+                        ))
+                        .monospace()
+                        .weak(),
+                    );
+                    ui.label(
+                        egui::RichText::new(&notification.message).color(notification.kind.color()),
+                    );
+                });
+            }
+        });
+    }
+}
+
+fn describe_outcome(name: &str, pid: i32, outcome: &KillOutcome) -> (NotificationKind, String) {
+    if outcome.already_dead {
+        return (
+            NotificationKind::Warning,
+            format!("{} ({}) had already exited", name, pid),
+        );
+    }
+
+    let Some(signal) = outcome.effective_signal else {
+        return (
+            NotificationKind::Error,
+            format!(
+                "{} ({}) is still running after {:.1}s of signals",
+                name,
+                pid,
+                outcome.elapsed.as_secs_f32()
+            ),
+        );
+    };
+
+    let mut message = format!(
+        "{} ({}) exited on {} after {:.2}s",
+        name,
+        pid,
+        signal.as_str(),
+        outcome.elapsed.as_secs_f32()
+    );
+    if outcome.zombie {
+        message.push_str(", now a zombie waiting for its parent to reap it");
+        return (NotificationKind::Warning, message);
+    }
+
+    (NotificationKind::Success, message)
+}
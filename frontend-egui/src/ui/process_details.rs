@@ -0,0 +1,302 @@
+//! Detail window for a single process, with an overview and its resource limits.
+
+use crate::ui::affinity_editor::format_cpu_list;
+use crate::ui::process_table::{format_age, format_memory, format_rate};
+use backend::{
+    cpu_affinity, io_priority, limits, memory_details, Limit, LimitUnit, ProcessInfo, Resource,
+};
+use eframe::egui;
+use std::path::Path;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Tab {
+    Overview,
+    Limits,
+}
+
+/// One row of the "Limits" tab, with the text being edited.
+struct LimitRow {
+    resource: Resource,
+    current: Limit,
+    soft: String,
+    hard: String,
+}
+
+impl LimitRow {
+    fn new(resource: Resource, current: Limit) -> Self {
+        Self {
+            resource,
+            current,
+            soft: format_limit_value(current.soft),
+            hard: format_limit_value(current.hard),
+        }
+    }
+
+    /// The edited limit, if both values parse.
+    fn edited(&self) -> Option<Limit> {
+        Some(Limit {
+            soft: parse_limit_value(&self.soft)?,
+            hard: parse_limit_value(&self.hard)?,
+        })
+    }
+}
+
+pub struct ProcessDetails {
+    pub process: ProcessInfo,
+    tab: Tab,
+    limits: Result<Vec<LimitRow>, String>,
+}
+
+impl ProcessDetails {
+    pub fn new(process: &ProcessInfo) -> Self {
+        let mut process = process.clone();
+        // Cheap enough for one process even when the table does not read it
+        if process.memory_details.is_none() {
+            process.memory_details = memory_details(process.pid).ok();
+        }
+        if process.cpu_affinity.is_none() {
+            process.cpu_affinity = cpu_affinity(process.pid).ok();
+        }
+        if process.io_priority.is_none() {
+            process.io_priority = io_priority(process.pid).ok();
+        }
+        let mut details = Self {
+            process,
+            tab: Tab::Overview,
+            limits: Ok(Vec::new()),
+        };
+        details.reload();
+        details
+    }
+
+    /// Read the resource limits again, dropping unapplied edits.
+    pub fn reload(&mut self) {
+        self.limits = limits(self.process.pid)
+            .map(|limits| {
+                limits
+                    .into_iter()
+                    .map(|(resource, limit)| LimitRow::new(resource, limit))
+                    .collect()
+            })
+            .map_err(|e| e.to_string());
+    }
+
+    /// Show the window. Returns a limit the user chose to apply; `open` is
+    /// cleared when the window is closed.
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) -> Option<(Resource, Limit)> {
+        let mut applied = None;
+
+        egui::Window::new(format!("{} ({})", self.process.name, self.process.pid))
+            .id(egui::Id::new("process_details"))
+            .open(open)
+            .default_size(egui::vec2(560.0, 420.0))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.tab, Tab::Overview, "Overview");
+                    ui.selectable_value(&mut self.tab, Tab::Limits, "Limits");
+                });
+                ui.separator();
+
+                match self.tab {
+                    Tab::Overview => show_overview(ui, &self.process),
+                    Tab::Limits => applied = self.show_limits(ui),
+                }
+            });
+
+        applied
+    }
+
+    fn show_limits(&mut self, ui: &mut egui::Ui) -> Option<(Resource, Limit)> {
+        let mut applied = None;
+        let mut reload = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Leave a value empty or type \"unlimited\" to remove the limit.");
+            if ui.button("Refresh").clicked() {
+                reload = true;
+            }
+        });
+        ui.add_space(4.0);
+
+        match &mut self.limits {
+            Err(e) => {
+                ui.colored_label(egui::Color32::from_rgb(230, 70, 70), e.as_str());
+            }
+            Ok(rows) => {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("limits_grid")
+                        .striped(true)
+                        .num_columns(5)
+                        .show(ui, |ui| {
+                            for title in ["Resource", "Soft", "Hard", "Unit", ""] {
+                                ui.strong(title);
+                            }
+                            ui.end_row();
+
+                            for row in rows.iter_mut() {
+                                ui.label(row.resource.description())
+                                    .on_hover_text(row.resource.name());
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut row.soft).desired_width(120.0),
+                                );
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut row.hard).desired_width(120.0),
+                                );
+                                ui.label(unit_label(row.resource.unit()));
+
+                                let edited = row.edited();
+                                let changed = edited.is_some_and(|limit| limit != row.current);
+                                if ui
+                                    .add_enabled(changed, egui::Button::new("Apply"))
+                                    .on_disabled_hover_text(
+                                        "Change a value first, to a number or \"unlimited\"",
+                                    )
+                                    .clicked()
+                                {
+                                    applied = edited.map(|limit| (row.resource, limit));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
+        }
+
+        if reload {
+            self.reload();
+        }
+        applied
+    }
+}
+
+fn show_overview(ui: &mut egui::Ui, p: &ProcessInfo) {
+    let memory = p.memory_details;
+    let (io, rates) = (p.io, p.io_rates);
+    egui::Grid::new("overview_grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            let rows = [
+                ("PID", p.pid.to_string()),
+                ("Name", p.name.clone()),
+                ("Command line", p.cmdline.join(" ")),
+                ("Executable", display_path(p.exe.as_deref())),
+                ("Working directory", display_path(p.cwd.as_deref())),
+                ("User", user_text(&p.user, p.uid)),
+                ("Effective user", user_text(&p.effective_user, p.euid)),
+                ("Running for", format_age(p.age)),
+                ("Threads", p.threads.to_string()),
+                ("State", p.state.clone()),
+                ("Parent PID", p.ppid.to_string()),
+                ("Process group", p.pgid.to_string()),
+                ("Session", p.sid.to_string()),
+                ("CPU", format!("{:.1} %", p.cpu_percent)),
+                ("Resident memory (RSS)", format_memory(Some(p.memory_bytes))),
+                ("Proportional (PSS)", format_memory(memory.map(|m| m.pss))),
+                ("Unique (USS)", format_memory(memory.map(|m| m.uss))),
+                ("Shared", format_memory(memory.map(|m| m.shared))),
+                ("Swap", format_memory(memory.map(|m| m.swap))),
+                ("Swap PSS", format_memory(memory.map(|m| m.swap_pss))),
+                (
+                    "Disk read",
+                    io_text(io.map(|io| io.read_bytes), rates.map(|r| r.read_bytes)),
+                ),
+                (
+                    "Disk written",
+                    io_text(io.map(|io| io.write_bytes), rates.map(|r| r.write_bytes)),
+                ),
+                (
+                    "Cancelled writes",
+                    io_text(
+                        io.map(|io| io.cancelled_write_bytes),
+                        rates.map(|r| r.cancelled_write_bytes),
+                    ),
+                ),
+                (
+                    "Read syscalls",
+                    syscall_text(
+                        io.map(|io| io.read_syscalls),
+                        rates.map(|r| r.read_syscalls),
+                    ),
+                ),
+                (
+                    "Write syscalls",
+                    syscall_text(
+                        io.map(|io| io.write_syscalls),
+                        rates.map(|r| r.write_syscalls),
+                    ),
+                ),
+                ("Nice", p.nice.to_string()),
+                ("Kernel priority", p.priority.to_string()),
+                ("Scheduling policy", p.sched_policy.to_string()),
+                (
+                    "CPU affinity",
+                    p.cpu_affinity
+                        .as_deref()
+                        .map_or_else(|| "unknown".to_string(), format_cpu_list),
+                ),
+                (
+                    "I/O priority",
+                    p.io_priority
+                        .map_or_else(|| "unknown".to_string(), |priority| priority.to_string()),
+                ),
+                (
+                    "OOM score",
+                    format!("{} ({:+})", p.oom_score, p.oom_score_adj),
+                ),
+            ];
+            for (label, value) in rows {
+                ui.strong(label);
+                ui.label(value);
+                ui.end_row();
+            }
+        });
+}
+
+fn display_path(path: Option<&Path>) -> String {
+    path.map_or_else(|| "unknown".to_string(), |path| path.display().to_string())
+}
+
+/// Total bytes and the current rate, e.g. "12.0 MB (1.5 KB/s)".
+fn io_text(total: Option<u64>, rate: Option<f64>) -> String {
+    match total {
+        Some(total) => format!("{} ({})", format_memory(Some(total)), format_rate(rate)),
+        None => "unknown".to_string(),
+    }
+}
+
+fn syscall_text(total: Option<u64>, rate: Option<f64>) -> String {
+    match (total, rate) {
+        (Some(total), Some(rate)) => format!("{} ({:.0}/s)", total, rate),
+        (Some(total), None) => total.to_string(),
+        _ => "unknown".to_string(),
+    }
+}
+
+fn user_text(name: &str, uid: u32) -> String {
+    format!("{} ({})", name, uid)
+}
+
+fn unit_label(unit: LimitUnit) -> &'static str {
+    match unit {
+        LimitUnit::Bytes => "bytes",
+        LimitUnit::Seconds => "seconds",
+        LimitUnit::Microseconds => "µs",
+        LimitUnit::Count => "",
+    }
+}
+
+fn format_limit_value(value: Option<u64>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "unlimited".to_string(),
+    }
+}
+
+/// Parse an edited limit value: `Some(None)` for unlimited, `None` if invalid.
+fn parse_limit_value(text: &str) -> Option<Option<u64>> {
+    let text = text.trim();
+    if text.is_empty() || text.eq_ignore_ascii_case("unlimited") {
+        return Some(None);
+    }
+    text.parse().ok().map(Some)
+}
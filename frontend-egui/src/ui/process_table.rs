@@ -1,11 +1,23 @@
 //! Process table component with sorting and kill functionality.
 
-use backend::{kill_pid, ProcessInfo};
+use crate::ui::affinity_editor::{format_cpu_list, AffinityEditor};
+use crate::ui::notifications::{NotificationKind, NotificationLog};
+use crate::ui::process_details::ProcessDetails;
+use backend::{
+    cgroup_of, cpu_affinity, io_priority, kill_group, kill_pid, kill_session, protection_reason,
+    resume_cgroup, resume_pid, resume_tree, send_signal, send_thread_signal, set_cpu_affinity,
+    set_io_priority, set_limit, set_nice, set_oom_score_adj, set_sched_policy, suspend_cgroup,
+    suspend_pid, suspend_tree, IoPriority, KillPolicy, Limit, PidOutcome, ProcError, ProcessInfo,
+    ProtectionReason, Resource, Safety, SchedPolicy, Signal, SignalKind, SnapshotDiff, Suspension,
+    ThreadInfo, ThreadScope, Trash, TrashItem, IO_LEVEL_RANGE, OOM_SCORE_ADJ_RANGE,
+    RT_PRIORITY_RANGE,
+};
 use eframe::egui;
-use egui_extras::{Column, TableBuilder};
+use egui_extras::{Column, TableBuilder, TableRow};
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 // Filter processes based on search text
 fn filter_processes(processes: &[ProcessInfo], search_text: &str) -> Vec<ProcessInfo> {
@@ -25,6 +37,7 @@ fn filter_processes(processes: &[ProcessInfo], search_text: &str) -> Vec<Process
 }
 
 #[derive(Default, PartialEq, Eq, Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum SortColumn {
     #[default]
     PID,
@@ -32,15 +45,296 @@ pub enum SortColumn {
     CPU,
     Memory,
     State,
+    IO,
+    OOM,
     PPID,
+    User,
+    Threads,
+    Nice,
+    Priority,
+    Age,
+    DiskRead,
+    DiskWrite,
+    Cmdline,
+    Exe,
+    Cwd,
+}
+
+/// How long a new row stays highlighted, and an exited one stays listed.
+const ROW_FADE: Duration = Duration::from_millis(1500);
+
+/// Signals offered in the "Send signal" menu, with what they usually do.
+const COMMON_SIGNALS: [(Signal, &str); 11] = [
+    (Signal::SIGHUP, "Reload configuration (most daemons)"),
+    (Signal::SIGUSR1, "User-defined; often reopens logs"),
+    (Signal::SIGUSR2, "User-defined"),
+    (Signal::SIGINT, "Interrupt, like Ctrl+C"),
+    (Signal::SIGQUIT, "Quit and dump core, like Ctrl+\\"),
+    (Signal::SIGTERM, "Ask to terminate"),
+    (Signal::SIGKILL, "Terminate immediately"),
+    (Signal::SIGSTOP, "Pause"),
+    (Signal::SIGTSTP, "Pause, like Ctrl+Z"),
+    (Signal::SIGCONT, "Resume a paused process"),
+    (Signal::SIGWINCH, "Terminal resized"),
+];
+
+/// Nice values offered in the "Priority" menu.
+const NICE_PRESETS: [(&str, i32); 5] = [
+    ("Highest", -20),
+    ("High", -10),
+    ("Normal", 0),
+    ("Low", 10),
+    ("Lowest", 19),
+];
+
+/// `oom_score_adj` values offered in the "OOM score" menu.
+const OOM_PRESETS: [(&str, i32); 5] = [
+    ("Never kill", -1000),
+    ("Protect", -500),
+    ("Default", 0),
+    ("Prefer killing", 500),
+    ("Kill first", 1000),
+];
+
+/// Kill policy presets offered in the context menu.
+#[derive(Default, PartialEq, Eq, Clone, Copy)]
+pub enum KillPreset {
+    Graceful,
+    #[default]
+    Default,
+    Immediate,
+}
+
+impl KillPreset {
+    const ALL: [KillPreset; 3] = [
+        KillPreset::Graceful,
+        KillPreset::Default,
+        KillPreset::Immediate,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            KillPreset::Graceful => "Graceful",
+            KillPreset::Default => "Default",
+            KillPreset::Immediate => "Immediate",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            KillPreset::Graceful => "SIGTERM, up to 30 s to shut down, then SIGKILL",
+            KillPreset::Default => "SIGSTOP, SIGTERM, 500 ms, then SIGKILL",
+            KillPreset::Immediate => "SIGKILL right away",
+        }
+    }
+
+    pub fn policy(self) -> KillPolicy {
+        match self {
+            KillPreset::Graceful => KillPolicy::graceful(),
+            KillPreset::Default => KillPolicy::default(),
+            KillPreset::Immediate => KillPolicy::immediate(),
+        }
+    }
+}
+
+/// Which memory figure the Memory column and the status bar total show.
+#[derive(Default, PartialEq, Eq, Clone, Copy)]
+pub enum MemoryMetric {
+    #[default]
+    Rss,
+    Pss,
+    Uss,
+    Shared,
+    Swap,
+    SwapPss,
+}
+
+impl MemoryMetric {
+    pub const ALL: [MemoryMetric; 6] = [
+        MemoryMetric::Rss,
+        MemoryMetric::Pss,
+        MemoryMetric::Uss,
+        MemoryMetric::Shared,
+        MemoryMetric::Swap,
+        MemoryMetric::SwapPss,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MemoryMetric::Rss => "RSS",
+            MemoryMetric::Pss => "PSS",
+            MemoryMetric::Uss => "USS",
+            MemoryMetric::Shared => "Shared",
+            MemoryMetric::Swap => "Swap",
+            MemoryMetric::SwapPss => "Swap PSS",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            MemoryMetric::Rss => "Resident memory, counting shared pages in full for every process",
+            MemoryMetric::Pss => {
+                "Private memory plus a fair share of shared memory; adds up across processes"
+            }
+            MemoryMetric::Uss => "Memory used by this process alone, freed if it is killed",
+            MemoryMetric::Shared => "Resident memory also mapped by other processes",
+            MemoryMetric::Swap => "Memory swapped out to disk",
+            MemoryMetric::SwapPss => {
+                "Swapped-out memory with shared pages divided up; adds up across processes"
+            }
+        }
+    }
+
+    /// Whether the metric needs the slower detailed memory accounting.
+    pub fn is_detailed(self) -> bool {
+        self != MemoryMetric::Rss
+    }
+
+    /// The metric for `p`, or `None` if its details could not be read.
+    pub fn value(self, p: &ProcessInfo) -> Option<u64> {
+        match self {
+            MemoryMetric::Rss => Some(p.memory_bytes),
+            MemoryMetric::Pss => p.memory_details.map(|m| m.pss),
+            MemoryMetric::Uss => p.memory_details.map(|m| m.uss),
+            MemoryMetric::Shared => p.memory_details.map(|m| m.shared),
+            MemoryMetric::Swap => p.memory_details.map(|m| m.swap),
+            MemoryMetric::SwapPss => p.memory_details.map(|m| m.swap_pss),
+        }
+    }
+}
+
+/// What a pause, resume or trash action applies to.
+#[derive(Clone, Copy)]
+enum Scope {
+    Process,
+    Tree,
+    Cgroup,
+}
+
+impl Scope {
+    const ALL: [Scope; 3] = [Scope::Process, Scope::Tree, Scope::Cgroup];
+
+    fn label(self) -> &'static str {
+        match self {
+            Scope::Process => "Process",
+            Scope::Tree => "Process tree",
+            Scope::Cgroup => "Cgroup",
+        }
+    }
+
+    fn noun(self) -> &'static str {
+        match self {
+            Scope::Process => "",
+            Scope::Tree => " tree",
+            Scope::Cgroup => " cgroup",
+        }
+    }
+}
+
+/// An action started from the context menu.
+#[derive(Clone)]
+enum Action {
+    Kill(KillPolicy),
+    KillGroup(KillPolicy),
+    KillSession(KillPolicy),
+    Signal(SignalKind),
+    /// Signal a single thread, by TID.
+    SignalThread(i32, SignalKind),
+    Trash(Scope),
+    Pause(Scope),
+    Resume(Scope),
+    Renice(i32, ThreadScope),
+    SetPolicy(SchedPolicy, ThreadScope),
+    SetAffinity(Vec<usize>, ThreadScope),
+    SetIoPriority(IoPriority, ThreadScope),
+    SetOomScoreAdj(i32),
+    SetLimit(Resource, Limit),
+}
+
+impl Action {
+    fn label(&self) -> String {
+        match self {
+            Action::Kill(_) => "Kill".to_string(),
+            Action::KillGroup(_) => "Kill process group".to_string(),
+            Action::KillSession(_) => "Kill session".to_string(),
+            Action::Signal(signal) => format!("Send {}", signal),
+            Action::SignalThread(tid, signal) => format!("Send {} to thread {}", signal, tid),
+            Action::Trash(scope) => format!("Move{} to trash", scope.noun()),
+            Action::Pause(scope) => format!("Pause{}", scope.noun()),
+            Action::Resume(scope) => format!("Resume{}", scope.noun()),
+            Action::Renice(nice, scope) => {
+                format!("Set nice{} to {}", thread_suffix(*scope), nice)
+            }
+            Action::SetPolicy(policy, scope) => {
+                format!("Set policy{} to {}", thread_suffix(*scope), policy)
+            }
+            Action::SetAffinity(cpus, scope) => format!(
+                "Set CPU affinity{} to {}",
+                thread_suffix(*scope),
+                format_cpu_list(cpus)
+            ),
+            Action::SetIoPriority(priority, scope) => {
+                format!("Set I/O priority{} to {}", thread_suffix(*scope), priority)
+            }
+            Action::SetOomScoreAdj(adj) => format!("Set OOM score adjustment to {:+}", adj),
+            Action::SetLimit(resource, _) => format!("Change {}", resource),
+        }
+    }
+}
+
+/// An action on a protected process waiting for the user to confirm it.
+struct PendingOverride {
+    process: ProcessInfo,
+    /// The protected process: `process` itself, or a member of its group,
+    /// session, tree or cgroup that the action also reaches.
+    protected: i32,
+    reason: ProtectionReason,
+    action: Action,
 }
 
 pub struct ProcessTable {
     pub sort_column: SortColumn,
     pub sort_descending: bool,
+    pub kill_preset: KillPreset,
+    /// What the Memory column shows.
+    pub memory_metric: MemoryMetric,
     pub killing: Arc<Mutex<HashSet<i32>>>,
+    pub notifications: NotificationLog,
+    pub trash: Arc<Mutex<Trash>>,
+    pending_override: Option<PendingOverride>,
+    /// Background actions refused over a protected member, to confirm in turn.
+    refused: Arc<Mutex<Vec<PendingOverride>>>,
+    /// Apply priority and affinity changes to every thread, not just the main one.
+    pub all_threads: bool,
+    /// Priority used when switching to a realtime policy.
+    pub rt_priority: i32,
+    /// Level used when switching to the realtime or best-effort I/O class.
+    pub io_level: i32,
+    /// Custom value offered in the "OOM score" menu.
+    pub oom_score_adj: i32,
+    affinity_editor: Option<AffinityEditor>,
+    details: Option<ProcessDetails>,
+    /// PIDs to highlight, e.g. the preview of a bulk kill.
+    pub highlighted: HashSet<i32>,
+    /// Processes expanded to show their threads.
+    pub expanded: HashSet<i32>,
+    /// When each new process was first seen, to flash its row green.
+    added: HashMap<i32, Instant>,
+    /// Processes that exited, kept as fading red rows for a moment.
+    exited: Vec<(ProcessInfo, Instant)>,
     pub show_pid: bool,
     pub show_ppid: bool,
+    pub show_user: bool,
+    pub show_threads: bool,
+    pub show_nice: bool,
+    pub show_priority: bool,
+    pub show_age: bool,
+    pub show_io_priority: bool,
+    pub show_disk_read: bool,
+    pub show_disk_write: bool,
+    pub show_cmdline: bool,
+    pub show_exe: bool,
+    pub show_cwd: bool,
 }
 
 impl Default for ProcessTable {
@@ -48,9 +342,36 @@ impl Default for ProcessTable {
         Self {
             sort_column: SortColumn::PID,
             sort_descending: false,
+            kill_preset: KillPreset::default(),
+            memory_metric: MemoryMetric::default(),
             killing: Arc::new(Mutex::new(HashSet::new())),
+            notifications: NotificationLog::default(),
+            trash: Arc::new(Mutex::new(Trash::new(Duration::from_secs(10 * 60)))),
+            pending_override: None,
+            refused: Arc::new(Mutex::new(Vec::new())),
+            all_threads: false,
+            rt_priority: 10,
+            io_level: 4,
+            oom_score_adj: 0,
+            affinity_editor: None,
+            details: None,
+            highlighted: HashSet::new(),
+            expanded: HashSet::new(),
+            added: HashMap::new(),
+            exited: Vec::new(),
             show_pid: false,
             show_ppid: false,
+            show_user: false,
+            show_threads: false,
+            show_nice: false,
+            show_priority: false,
+            show_age: false,
+            show_io_priority: false,
+            show_disk_read: false,
+            show_disk_write: false,
+            show_cmdline: false,
+            show_exe: false,
+            show_cwd: false,
         }
     }
 }
@@ -60,10 +381,17 @@ impl ProcessTable {
         &mut self,
         ui: &mut egui::Ui,
         processes: &[ProcessInfo],
+        threads: &HashMap<i32, Vec<ThreadInfo>>,
         search_text: &str,
     ) -> usize {
+        // Recently exited processes stay listed until they fade out
+        self.added.retain(|_, seen| seen.elapsed() < ROW_FADE);
+        self.exited.retain(|(_, seen)| seen.elapsed() < ROW_FADE);
+        let mut processes = processes.to_vec();
+        processes.extend(self.exited.iter().map(|(p, _)| p.clone()));
+
         // Filter processes first
-        let filtered_processes = filter_processes(processes, search_text);
+        let filtered_processes = filter_processes(&processes, search_text);
 
         // Sort filtered processes
         let mut sorted_processes = filtered_processes;
@@ -72,9 +400,24 @@ impl ProcessTable {
                 SortColumn::PID => a.pid.cmp(&b.pid),
                 SortColumn::Name => a.name.cmp(&b.name),
                 SortColumn::CPU => ord_f32(a.cpu_percent, b.cpu_percent),
-                SortColumn::Memory => a.memory_bytes.cmp(&b.memory_bytes),
+                SortColumn::Memory => self
+                    .memory_metric
+                    .value(a)
+                    .cmp(&self.memory_metric.value(b)),
                 SortColumn::State => a.state.cmp(&b.state),
+                SortColumn::IO => a.io_priority.cmp(&b.io_priority),
+                SortColumn::OOM => a.oom_score.cmp(&b.oom_score),
                 SortColumn::PPID => a.ppid.cmp(&b.ppid),
+                SortColumn::User => a.user.cmp(&b.user),
+                SortColumn::Threads => a.threads.cmp(&b.threads),
+                SortColumn::Nice => a.nice.cmp(&b.nice),
+                SortColumn::Priority => a.priority.cmp(&b.priority),
+                SortColumn::Age => a.age.cmp(&b.age),
+                SortColumn::DiskRead => ord_rate(disk_read_rate(a), disk_read_rate(b)),
+                SortColumn::DiskWrite => ord_rate(disk_write_rate(a), disk_write_rate(b)),
+                SortColumn::Cmdline => a.cmdline.cmp(&b.cmdline),
+                SortColumn::Exe => a.exe.cmp(&b.exe),
+                SortColumn::Cwd => a.cwd.cmp(&b.cwd),
             };
             if self.sort_descending {
                 ord.reverse()
@@ -83,6 +426,34 @@ impl ProcessTable {
             }
         });
 
+        // Expanded processes are followed by their threads
+        let mut rows: Vec<(usize, Option<&ThreadInfo>)> = Vec::new();
+        for (idx, p) in sorted_processes.iter().enumerate() {
+            rows.push((idx, None));
+            if !self.expanded.contains(&p.pid) || self.exited_at(p).is_some() {
+                continue;
+            }
+            if let Some(threads) = threads.get(&p.pid) {
+                let mut threads: Vec<&ThreadInfo> = threads.iter().collect();
+                threads.sort_by(|a, b| {
+                    let ord = match self.sort_column {
+                        SortColumn::Name => a.name.cmp(&b.name),
+                        SortColumn::CPU => ord_f32(a.cpu_percent, b.cpu_percent),
+                        SortColumn::State => a.state.cmp(&b.state),
+                        SortColumn::Nice => a.nice.cmp(&b.nice),
+                        SortColumn::Priority => a.priority.cmp(&b.priority),
+                        _ => a.tid.cmp(&b.tid),
+                    };
+                    if self.sort_descending {
+                        ord.reverse()
+                    } else {
+                        ord
+                    }
+                });
+                rows.extend(threads.into_iter().map(|t| (idx, Some(t))));
+            }
+        }
+
         // Build a real table: fixed columns, striped rows, consistent layout
         let text_sz = 16.0;
         let row_height = 30.0;
@@ -100,9 +471,43 @@ impl ProcessTable {
             .column(Column::exact(80.0)) // CPU
             .column(Column::exact(110.0)) // Memory
             .column(Column::exact(90.0)); // State
+        if self.show_io_priority {
+            table_builder = table_builder.column(Column::exact(120.0)); // I/O priority
+        }
+        table_builder = table_builder.column(Column::exact(110.0)); // OOM score
         if self.show_ppid {
             table_builder = table_builder.column(Column::exact(80.0)); // PPID
         }
+        if self.show_user {
+            table_builder = table_builder.column(Column::exact(110.0)); // User
+        }
+        if self.show_threads {
+            table_builder = table_builder.column(Column::exact(90.0)); // Threads
+        }
+        if self.show_nice {
+            table_builder = table_builder.column(Column::exact(60.0)); // Nice
+        }
+        if self.show_priority {
+            table_builder = table_builder.column(Column::exact(90.0)); // Priority
+        }
+        if self.show_age {
+            table_builder = table_builder.column(Column::exact(90.0)); // Age
+        }
+        if self.show_disk_read {
+            table_builder = table_builder.column(Column::exact(120.0)); // Disk read/s
+        }
+        if self.show_disk_write {
+            table_builder = table_builder.column(Column::exact(120.0)); // Disk write/s
+        }
+        if self.show_cmdline {
+            table_builder = table_builder.column(Column::exact(300.0)); // Command line
+        }
+        if self.show_exe {
+            table_builder = table_builder.column(Column::exact(220.0)); // Executable
+        }
+        if self.show_cwd {
+            table_builder = table_builder.column(Column::exact(220.0)); // Working directory
+        }
 
         table_builder
             .header(row_height, |mut header| {
@@ -138,7 +543,7 @@ impl ProcessTable {
                 header.col(|ui| {
                     sort_header(
                         ui,
-                        "Memory",
+                        &format!("Memory ({})", self.memory_metric.label()),
                         SortColumn::Memory,
                         &mut self.sort_column,
                         &mut self.sort_descending,
@@ -153,6 +558,26 @@ impl ProcessTable {
                         &mut self.sort_descending,
                     )
                 });
+                if self.show_io_priority {
+                    header.col(|ui| {
+                        sort_header(
+                            ui,
+                            "I/O",
+                            SortColumn::IO,
+                            &mut self.sort_column,
+                            &mut self.sort_descending,
+                        )
+                    });
+                }
+                header.col(|ui| {
+                    sort_header(
+                        ui,
+                        "OOM",
+                        SortColumn::OOM,
+                        &mut self.sort_column,
+                        &mut self.sort_descending,
+                    )
+                });
                 if self.show_ppid {
                     header.col(|ui| {
                         sort_header(
@@ -164,11 +589,126 @@ impl ProcessTable {
                         )
                     });
                 }
+                if self.show_user {
+                    header.col(|ui| {
+                        sort_header(
+                            ui,
+                            "User",
+                            SortColumn::User,
+                            &mut self.sort_column,
+                            &mut self.sort_descending,
+                        )
+                    });
+                }
+                if self.show_threads {
+                    header.col(|ui| {
+                        sort_header(
+                            ui,
+                            "Threads",
+                            SortColumn::Threads,
+                            &mut self.sort_column,
+                            &mut self.sort_descending,
+                        )
+                    });
+                }
+                if self.show_nice {
+                    header.col(|ui| {
+                        sort_header(
+                            ui,
+                            "Nice",
+                            SortColumn::Nice,
+                            &mut self.sort_column,
+                            &mut self.sort_descending,
+                        )
+                    });
+                }
+                if self.show_priority {
+                    header.col(|ui| {
+                        sort_header(
+                            ui,
+                            "Priority",
+                            SortColumn::Priority,
+                            &mut self.sort_column,
+                            &mut self.sort_descending,
+                        )
+                    });
+                }
+                if self.show_age {
+                    header.col(|ui| {
+                        sort_header(
+                            ui,
+                            "Age",
+                            SortColumn::Age,
+                            &mut self.sort_column,
+                            &mut self.sort_descending,
+                        )
+                    });
+                }
+                if self.show_disk_read {
+                    header.col(|ui| {
+                        sort_header(
+                            ui,
+                            "Disk Read/s",
+                            SortColumn::DiskRead,
+                            &mut self.sort_column,
+                            &mut self.sort_descending,
+                        )
+                    });
+                }
+                if self.show_disk_write {
+                    header.col(|ui| {
+                        sort_header(
+                            ui,
+                            "Disk Write/s",
+                            SortColumn::DiskWrite,
+                            &mut self.sort_column,
+                            &mut self.sort_descending,
+                        )
+                    });
+                }
+                if self.show_cmdline {
+                    header.col(|ui| {
+                        sort_header(
+                            ui,
+                            "Command line",
+                            SortColumn::Cmdline,
+                            &mut self.sort_column,
+                            &mut self.sort_descending,
+                        )
+                    });
+                }
+                if self.show_exe {
+                    header.col(|ui| {
+                        sort_header(
+                            ui,
+                            "Executable",
+                            SortColumn::Exe,
+                            &mut self.sort_column,
+                            &mut self.sort_descending,
+                        )
+                    });
+                }
+                if self.show_cwd {
+                    header.col(|ui| {
+                        sort_header(
+                            ui,
+                            "Working dir",
+                            SortColumn::Cwd,
+                            &mut self.sort_column,
+                            &mut self.sort_descending,
+                        )
+                    });
+                }
             })
             .body(|body| {
-                body.rows(row_height, sorted_processes.len(), |mut row| {
-                    let idx = row.index();
+                body.rows(row_height, rows.len(), |mut row| {
+                    let (idx, thread) = rows[row.index()];
                     let p = &sorted_processes[idx];
+                    row.set_selected(self.highlighted.contains(&p.pid));
+                    if let Some(t) = thread {
+                        self.show_thread_row(&mut row, p, t, text_sz);
+                        return;
+                    }
 
                     // PID column - conditionally shown, NO right-click menu
                     if self.show_pid {
@@ -182,19 +722,38 @@ impl ProcessTable {
                     }
 
                     // Name column - WITH right-click menu
+                    let mut clip_rect = egui::Rect::EVERYTHING;
                     row.col(|ui| {
+                        clip_rect = ui.clip_rect();
+                        if p.threads > 1 && self.exited_at(p).is_none() {
+                            let expanded = self.expanded.contains(&p.pid);
+                            let icon = if expanded { "⏷" } else { "⏵" };
+                            if ui
+                                .small_button(icon)
+                                .on_hover_text(format!("Show its {} threads", p.threads))
+                                .clicked()
+                            {
+                                if expanded {
+                                    self.expanded.remove(&p.pid);
+                                } else {
+                                    self.expanded.insert(p.pid);
+                                }
+                            }
+                        }
                         let response = ui.add(
                             egui::Label::new(egui::RichText::new(&p.name).size(text_sz))
                                 .sense(egui::Sense::click()),
                         );
-                        response.clone().on_hover_text(format!(
-                            "{}\nPID: {}\nRight-click for options",
-                            p.name, p.pid
-                        ));
+                        let hint = if self.exited_at(p).is_some() {
+                            "Exited"
+                        } else {
+                            "Right-click for options"
+                        };
+                        response
+                            .clone()
+                            .on_hover_text(format!("{}\nPID: {}\n{}", p.name, p.pid, hint));
 
-                        response.context_menu(|ui| {
-                            self.show_context_menu(ui, p);
-                        });
+                        self.row_context_menu(&response, p);
                     });
 
                     // CPU column - WITH right-click menu
@@ -206,39 +765,67 @@ impl ProcessTable {
                             .sense(egui::Sense::click()),
                         );
 
-                        response.context_menu(|ui| {
-                            self.show_context_menu(ui, p);
-                        });
+                        self.row_context_menu(&response, p);
                     });
 
                     // Memory column - WITH right-click menu
                     row.col(|ui| {
                         let response = ui.add(
                             egui::Label::new(
-                                egui::RichText::new(format!(
-                                    "{:.1} MB",
-                                    p.memory_bytes as f32 / (1024.0 * 1024.0)
-                                ))
-                                .size(text_sz),
+                                egui::RichText::new(format_memory(self.memory_metric.value(p)))
+                                    .size(text_sz),
                             )
                             .sense(egui::Sense::click()),
                         );
 
-                        response.context_menu(|ui| {
-                            self.show_context_menu(ui, p);
-                        });
+                        self.row_context_menu(&response, p);
                     });
 
                     // State column - WITH right-click menu
                     row.col(|ui| {
-                        let response = ui.add(
-                            egui::Label::new(egui::RichText::new(&p.state).size(text_sz))
+                        let mut response = ui.add(
+                            egui::Label::new(egui::RichText::new(state_text(p)).size(text_sz))
                                 .sense(egui::Sense::click()),
                         );
+                        if let Some(suspension) = p.suspended {
+                            response = response.on_hover_text(describe_suspension(suspension));
+                        }
+
+                        self.row_context_menu(&response, p);
+                    });
+
+                    // I/O priority column - WITH right-click menu
+                    if self.show_io_priority {
+                        row.col(|ui| {
+                            let text = p
+                                .io_priority
+                                .map_or_else(|| "-".to_string(), |priority| priority.to_string());
+                            let response = ui.add(
+                                egui::Label::new(egui::RichText::new(text).size(text_sz))
+                                    .sense(egui::Sense::click()),
+                            );
 
-                        response.context_menu(|ui| {
-                            self.show_context_menu(ui, p);
+                            self.row_context_menu(&response, p);
                         });
+                    }
+
+                    // OOM score column - WITH right-click menu
+                    row.col(|ui| {
+                        let text = if p.oom_score_adj == 0 {
+                            p.oom_score.to_string()
+                        } else {
+                            format!("{} ({:+})", p.oom_score, p.oom_score_adj)
+                        };
+                        let response = ui
+                            .add(
+                                egui::Label::new(egui::RichText::new(text).size(text_sz))
+                                    .sense(egui::Sense::click()),
+                            )
+                            .on_hover_text(
+                                "When memory runs out, the highest score is killed first",
+                            );
+
+                        self.row_context_menu(&response, p);
                     });
 
                     // PPID column - conditionally shown, WITH right-click menu
@@ -253,63 +840,1011 @@ impl ProcessTable {
                                 .sense(egui::Sense::click()),
                             );
 
-                            response.context_menu(|ui| {
-                                self.show_context_menu(ui, p);
-                            });
+                            self.row_context_menu(&response, p);
                         });
                     }
-                });
-            });
 
-        // Return the count of filtered processes
-        sorted_processes.len()
-    }
+                    // User column - conditionally shown, WITH right-click menu
+                    if self.show_user {
+                        row.col(|ui| {
+                            let mut response = ui.add(
+                                egui::Label::new(egui::RichText::new(&p.user).size(text_sz))
+                                    .sense(egui::Sense::click()),
+                            );
+                            if p.euid != p.uid {
+                                response = response
+                                    .on_hover_text(format!("Running as {}", p.effective_user));
+                            }
 
-    fn show_context_menu(&mut self, ui: &mut egui::Ui, p: &ProcessInfo) {
-        ui.set_min_width(200.0);
+                            self.row_context_menu(&response, p);
+                        });
+                    }
 
-        let is_killing = self.killing.lock().unwrap().contains(&p.pid);
+                    // Threads column - conditionally shown, WITH right-click menu
+                    if self.show_threads {
+                        row.col(|ui| {
+                            let response = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(p.threads.to_string())
+                                        .size(text_sz)
+                                        .monospace(),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
 
-        let kill_text = if is_killing {
-            "Killing..."
-        } else {
-            "Kill Process"
-        };
-        let kill_button = ui.add_enabled(
-            !is_killing,
-            egui::Button::new(kill_text)
-                .fill(egui::Color32::from_rgb(200, 40, 40))
-                .min_size(egui::vec2(180.0, 25.0)),
-        );
+                            self.row_context_menu(&response, p);
+                        });
+                    }
 
-        if kill_button.clicked() {
-            self.killing.lock().unwrap().insert(p.pid);
-            let pid = p.pid;
-            let killing = self.killing.clone();
+                    // Nice column - conditionally shown, WITH right-click menu
+                    if self.show_nice {
+                        row.col(|ui| {
+                            let response = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(p.nice.to_string())
+                                        .size(text_sz)
+                                        .monospace(),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
 
-            tokio::task::spawn_blocking(move || {
-                let _ = kill_pid(pid);
-                killing.lock().unwrap().remove(&pid);
-            });
+                            self.row_context_menu(&response, p);
+                        });
+                    }
 
-            ui.close_menu();
-        }
+                    // Priority column - conditionally shown, WITH right-click menu
+                    if self.show_priority {
+                        row.col(|ui| {
+                            let response = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(p.priority.to_string())
+                                        .size(text_sz)
+                                        .monospace(),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
 
-        ui.separator();
-        ui.label(format!("PID: {}", p.pid));
-        ui.label(format!("Name: {}", p.name));
-        ui.label(format!("State: {}", p.state));
-        ui.label(format!("Parent PID: {}", p.ppid));
-        ui.label(format!(
-            "Memory: {:.1} MB",
-            p.memory_bytes as f32 / (1024.0 * 1024.0)
-        ));
-    }
-}
+                            self.row_context_menu(&response, p);
+                        });
+                    }
 
-fn sort_header(
-    ui: &mut egui::Ui,
-    title: &str,
+                    // Age column - conditionally shown, WITH right-click menu
+                    if self.show_age {
+                        row.col(|ui| {
+                            let response = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(format_age(p.age))
+                                        .size(text_sz)
+                                        .monospace(),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
+
+                            self.row_context_menu(&response, p);
+                        });
+                    }
+
+                    // Disk read/s column - conditionally shown, WITH right-click menu
+                    if self.show_disk_read {
+                        row.col(|ui| {
+                            let response = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(format_rate(disk_read_rate(p)))
+                                        .size(text_sz)
+                                        .monospace(),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
+
+                            self.row_context_menu(&response, p);
+                        });
+                    }
+
+                    // Disk write/s column - conditionally shown, WITH right-click menu
+                    if self.show_disk_write {
+                        row.col(|ui| {
+                            let response = ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(format_rate(disk_write_rate(p)))
+                                        .size(text_sz)
+                                        .monospace(),
+                                )
+                                .sense(egui::Sense::click()),
+                            );
+
+                            self.row_context_menu(&response, p);
+                        });
+                    }
+
+                    // Command line column - conditionally shown, WITH right-click menu
+                    if self.show_cmdline {
+                        row.col(|ui| {
+                            let text = p.cmdline.join(" ");
+                            let response = ui
+                                .add(
+                                    egui::Label::new(egui::RichText::new(&text).size(text_sz))
+                                        .truncate(true)
+                                        .sense(egui::Sense::click()),
+                                )
+                                .on_hover_text(&text);
+
+                            self.row_context_menu(&response, p);
+                        });
+                    }
+
+                    // Executable column - conditionally shown, WITH right-click menu
+                    if self.show_exe {
+                        row.col(|ui| {
+                            let text = p
+                                .exe
+                                .as_ref()
+                                .map_or(String::new(), |exe| exe.display().to_string());
+                            let response = ui
+                                .add(
+                                    egui::Label::new(egui::RichText::new(&text).size(text_sz))
+                                        .truncate(true)
+                                        .sense(egui::Sense::click()),
+                                )
+                                .on_hover_text(&text);
+
+                            self.row_context_menu(&response, p);
+                        });
+                    }
+
+                    // Working directory column - conditionally shown, WITH right-click menu
+                    if self.show_cwd {
+                        row.col(|ui| {
+                            let text = p
+                                .cwd
+                                .as_ref()
+                                .map_or(String::new(), |cwd| cwd.display().to_string());
+                            let response = ui
+                                .add(
+                                    egui::Label::new(egui::RichText::new(&text).size(text_sz))
+                                        .truncate(true)
+                                        .sense(egui::Sense::click()),
+                                )
+                                .on_hover_text(&text);
+
+                            self.row_context_menu(&response, p);
+                        });
+                    }
+
+                    // Tint new and exited rows over their cells
+                    if let Some(tint) = self.row_tint(p) {
+                        let response = row.response();
+                        response
+                            .ctx
+                            .layer_painter(response.layer_id)
+                            .with_clip_rect(clip_rect)
+                            .rect_filled(response.rect, 0.0, tint);
+                    }
+                });
+            });
+
+        self.show_override_dialog(ui.ctx());
+        self.show_affinity_editor(ui.ctx());
+        self.show_details(ui.ctx());
+
+        // Keep repainting while kills run so their results show up promptly
+        if !self.killing.lock().unwrap().is_empty() {
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+        }
+        // Animate the fading rows
+        if !self.added.is_empty() || !self.exited.is_empty() {
+            ui.ctx().request_repaint();
+        }
+
+        // Return the count of filtered processes
+        let exited_rows = sorted_processes
+            .iter()
+            .filter(|p| self.exited_at(p).is_some())
+            .count();
+        sorted_processes.len() - exited_rows
+    }
+
+    /// Record what changed since the previous refresh, to flash new rows and
+    /// keep exited ones listed while they fade out.
+    pub fn apply_diff(&mut self, diff: &SnapshotDiff) {
+        let now = Instant::now();
+        for p in &diff.added {
+            self.added.insert(p.pid, now);
+        }
+        self.exited
+            .extend(diff.exited.iter().map(|p| (p.clone(), now)));
+        for p in &diff.exited {
+            self.expanded.remove(&p.pid);
+        }
+    }
+
+    /// When `p` was seen to exit, if it is one of the fading exited rows.
+    fn exited_at(&self, p: &ProcessInfo) -> Option<Instant> {
+        self.exited
+            .iter()
+            .find(|(gone, _)| gone.pid == p.pid && gone.start_time == p.start_time)
+            .map(|&(_, seen)| seen)
+    }
+
+    /// Overlay color of a new (green) or exited (red) row, fading with time.
+    fn row_tint(&self, p: &ProcessInfo) -> Option<egui::Color32> {
+        let (rgb, seen) = match self.exited_at(p) {
+            Some(seen) => ([230, 70, 70], seen),
+            None => ([70, 200, 90], *self.added.get(&p.pid)?),
+        };
+        let left = 1.0 - seen.elapsed().as_secs_f32() / ROW_FADE.as_secs_f32();
+        let alpha = (left.clamp(0.0, 1.0) * 90.0) as u8;
+        Some(egui::Color32::from_rgba_unmultiplied(
+            rgb[0], rgb[1], rgb[2], alpha,
+        ))
+    }
+
+    /// Offer the context menu on a cell of `p`'s row. Exited rows only stay
+    /// listed to fade out, so they offer no actions.
+    fn row_context_menu(&mut self, response: &egui::Response, p: &ProcessInfo) {
+        if self.exited_at(p).is_none() {
+            response.context_menu(|ui| {
+                self.show_context_menu(ui, p);
+            });
+        }
+    }
+
+    fn show_context_menu(&mut self, ui: &mut egui::Ui, p: &ProcessInfo) {
+        ui.set_min_width(200.0);
+
+        let is_killing = self.killing.lock().unwrap().contains(&p.pid);
+
+        let kill_text = if is_killing {
+            "Killing..."
+        } else {
+            "Kill Process"
+        };
+        let kill_button = ui.add_enabled(
+            !is_killing,
+            egui::Button::new(kill_text)
+                .fill(egui::Color32::from_rgb(200, 40, 40))
+                .min_size(egui::vec2(180.0, 25.0)),
+        );
+
+        if kill_button.clicked() {
+            self.request(p, Action::Kill(self.kill_preset.policy()));
+            ui.close_menu();
+        }
+
+        // Shell pipelines share a process group, terminal jobs a session
+        if ui
+            .add_enabled(!is_killing, egui::Button::new("Kill process group"))
+            .on_hover_text(format!("Every process in group {}", p.pgid))
+            .clicked()
+        {
+            self.request(p, Action::KillGroup(self.kill_preset.policy()));
+            ui.close_menu();
+        }
+        if ui
+            .add_enabled(!is_killing, egui::Button::new("Kill session"))
+            .on_hover_text(format!("Every process in session {}", p.sid))
+            .clicked()
+        {
+            self.request(p, Action::KillSession(self.kill_preset.policy()));
+            ui.close_menu();
+        }
+
+        ui.menu_button("Move to trash", |ui| {
+            ui.label("Stop it now; restore or kill it later from the trash");
+            for scope in Scope::ALL {
+                if ui.button(scope.label()).clicked() {
+                    self.request(p, Action::Trash(scope));
+                    ui.close_menu();
+                }
+            }
+        });
+
+        ui.menu_button("Pause", |ui| {
+            ui.label("Stop it from using CPU until resumed");
+            for scope in Scope::ALL {
+                if ui.button(scope.label()).clicked() {
+                    self.request(p, Action::Pause(scope));
+                    ui.close_menu();
+                }
+            }
+        });
+
+        ui.menu_button("Resume", |ui| {
+            for scope in Scope::ALL {
+                if ui.button(scope.label()).clicked() {
+                    // Resuming cannot hurt, so it never needs an override
+                    self.run(p, Action::Resume(scope), Safety::Enforce);
+                    ui.close_menu();
+                }
+            }
+        });
+
+        ui.menu_button("Priority", |ui| {
+            self.show_priority_menu(ui, p);
+        });
+
+        ui.menu_button("I/O priority", |ui| {
+            self.show_io_priority_menu(ui, p);
+        });
+
+        ui.menu_button("OOM score", |ui| {
+            self.show_oom_menu(ui, p);
+        });
+
+        ui.menu_button(format!("Kill policy: {}", self.kill_preset.label()), |ui| {
+            for preset in KillPreset::ALL {
+                ui.radio_value(&mut self.kill_preset, preset, preset.label())
+                    .on_hover_text(preset.description());
+            }
+        });
+
+        ui.menu_button("Send signal", |ui| {
+            self.show_signal_menu(ui, p, Action::Signal);
+        });
+
+        ui.separator();
+        if ui.button("Details...").clicked() {
+            self.details = Some(ProcessDetails::new(p));
+            ui.close_menu();
+        }
+        ui.label(format!("PID: {}", p.pid));
+        ui.label(format!("Name: {}", p.name));
+        ui.label(format!("State: {}", state_text(p)));
+        ui.label(format!("Parent PID: {}", p.ppid));
+        ui.label(format!("Nice: {}  Policy: {}", p.nice, p.sched_policy));
+        ui.label(format!("CPUs: {}", format_cpu_list(&affinity_of(p))));
+        ui.label(format!("I/O priority: {}", io_priority_of(p)));
+        ui.label(format!(
+            "OOM score: {} ({:+})",
+            p.oom_score, p.oom_score_adj
+        ));
+        ui.label(format!(
+            "Memory ({}): {}",
+            self.memory_metric.label(),
+            format_memory(self.memory_metric.value(p))
+        ));
+    }
+
+    /// Signals to choose from, each sent as the action `send` makes of it.
+    fn show_signal_menu(
+        &mut self,
+        ui: &mut egui::Ui,
+        p: &ProcessInfo,
+        send: impl Fn(SignalKind) -> Action,
+    ) {
+        for (signal, hint) in COMMON_SIGNALS {
+            if ui.button(signal.as_str()).on_hover_text(hint).clicked() {
+                self.request(p, send(signal.into()));
+                ui.close_menu();
+            }
+        }
+        ui.separator();
+        ui.menu_button("Realtime", |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for offset in 0..SignalKind::realtime_count() {
+                        let signal = SignalKind::Realtime(offset);
+                        if ui.button(signal.to_string()).clicked() {
+                            self.request(p, send(signal));
+                            ui.close_menu();
+                        }
+                    }
+                });
+        });
+    }
+
+    /// Cells of a thread row, under the row of its process. Columns that only
+    /// apply to whole processes are left empty.
+    fn show_thread_row(
+        &mut self,
+        row: &mut TableRow,
+        p: &ProcessInfo,
+        t: &ThreadInfo,
+        text_sz: f32,
+    ) {
+        let last_cpu = t
+            .last_cpu
+            .map_or_else(|| "unknown".to_string(), |cpu| cpu.to_string());
+
+        let mut cells = Vec::new();
+        if self.show_pid {
+            cells.push(t.tid.to_string());
+        }
+        cells.push(format!("{}  (CPU {})", t.name, last_cpu));
+        cells.push(format!("{:.1}", t.cpu_percent));
+        // Memory, I/O priority and OOM score belong to the process
+        cells.extend([String::new(), t.state.clone()]);
+        if self.show_io_priority {
+            cells.push(String::new());
+        }
+        cells.push(String::new());
+        let optional = [
+            (self.show_ppid, String::new()),
+            (self.show_user, String::new()),
+            (self.show_threads, String::new()),
+            (self.show_nice, t.nice.to_string()),
+            (self.show_priority, t.priority.to_string()),
+            (self.show_age, String::new()),
+            (self.show_disk_read, String::new()),
+            (self.show_disk_write, String::new()),
+            (self.show_cmdline, String::new()),
+            (self.show_exe, String::new()),
+            (self.show_cwd, String::new()),
+        ];
+        cells.extend(
+            optional
+                .into_iter()
+                .filter(|(shown, _)| *shown)
+                .map(|(_, text)| text),
+        );
+
+        let name_cell = usize::from(self.show_pid);
+        for (i, text) in cells.into_iter().enumerate() {
+            row.col(|ui| {
+                if i == name_cell {
+                    // Indent threads under their process
+                    ui.add_space(32.0);
+                }
+                let response = ui
+                    .add(
+                        egui::Label::new(egui::RichText::new(text).size(text_sz).weak())
+                            .sense(egui::Sense::click()),
+                    )
+                    .on_hover_text(format!(
+                        "Thread {} of {} ({})\nTID: {}\nLast ran on CPU {}\nRight-click for options",
+                        t.name, p.name, p.pid, t.tid, last_cpu
+                    ));
+
+                response.context_menu(|ui| {
+                    self.show_thread_menu(ui, p, t);
+                });
+            });
+        }
+    }
+
+    fn show_thread_menu(&mut self, ui: &mut egui::Ui, p: &ProcessInfo, t: &ThreadInfo) {
+        ui.set_min_width(200.0);
+        let tid = t.tid;
+        let scope = ThreadScope::Thread(tid);
+
+        ui.menu_button("Send signal", |ui| {
+            ui.label("Stopping and terminating still apply to the whole process");
+            ui.separator();
+            self.show_signal_menu(ui, p, |signal| Action::SignalThread(tid, signal));
+        });
+
+        ui.menu_button("Nice value", |ui| {
+            for (label, nice) in NICE_PRESETS {
+                if ui
+                    .selectable_label(t.nice == nice, format!("{} ({})", label, nice))
+                    .clicked()
+                {
+                    self.request(p, Action::Renice(nice, scope));
+                    ui.close_menu();
+                }
+            }
+        });
+
+        if ui.button("CPU affinity...").clicked() {
+            self.affinity_editor = Some(AffinityEditor::for_thread(p, t));
+            ui.close_menu();
+        }
+
+        ui.separator();
+        ui.label(format!("TID: {}", t.tid));
+        ui.label(format!("Name: {}", t.name));
+        ui.label(format!("Process: {} ({})", p.name, p.pid));
+        ui.label(format!("State: {}", t.state));
+        ui.label(format!("CPU: {:.1} %", t.cpu_percent));
+        ui.label(format!("Nice: {}  Policy: {}", t.nice, t.sched_policy));
+        ui.label(format!("CPUs: {}", format_cpu_list(&t.cpu_affinity)));
+    }
+
+    fn show_priority_menu(&mut self, ui: &mut egui::Ui, p: &ProcessInfo) {
+        let scope = self.thread_scope();
+
+        ui.label("Nice value");
+        for (label, nice) in NICE_PRESETS {
+            if ui
+                .selectable_label(p.nice == nice, format!("{} ({})", label, nice))
+                .clicked()
+            {
+                self.request(p, Action::Renice(nice, scope));
+                ui.close_menu();
+            }
+        }
+
+        ui.separator();
+        ui.label("Scheduling policy");
+        let policies = [
+            (
+                SchedPolicy::Other,
+                "Lets other processes share the CPU fairly",
+            ),
+            (SchedPolicy::Batch, "For background number crunching"),
+            (
+                SchedPolicy::Idle,
+                "Only runs when the CPU is otherwise idle",
+            ),
+            (
+                SchedPolicy::Fifo(self.rt_priority),
+                "Realtime: runs until it blocks or yields",
+            ),
+            (
+                SchedPolicy::RoundRobin(self.rt_priority),
+                "Realtime, sharing time slices with equal priorities",
+            ),
+        ];
+        for (policy, hint) in policies {
+            let current =
+                std::mem::discriminant(&p.sched_policy) == std::mem::discriminant(&policy);
+            if ui
+                .selectable_label(current, policy.to_string())
+                .on_hover_text(hint)
+                .clicked()
+            {
+                self.request(p, Action::SetPolicy(policy, scope));
+                ui.close_menu();
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label("Realtime priority");
+            ui.add(egui::DragValue::new(&mut self.rt_priority).clamp_range(RT_PRIORITY_RANGE));
+        });
+
+        ui.separator();
+        ui.checkbox(&mut self.all_threads, "Apply to all threads");
+        if ui.button("CPU affinity...").clicked() {
+            self.affinity_editor = Some(AffinityEditor::new(p));
+            ui.close_menu();
+        }
+    }
+
+    fn show_io_priority_menu(&mut self, ui: &mut egui::Ui, p: &ProcessInfo) {
+        let scope = self.thread_scope();
+
+        let classes = [
+            (
+                IoPriority::Realtime(self.io_level),
+                "Always gets the disk first",
+            ),
+            (
+                IoPriority::BestEffort(self.io_level),
+                "Takes turns with other processes",
+            ),
+            (
+                IoPriority::None,
+                "Best-effort, with a level that follows the nice value",
+            ),
+            (
+                IoPriority::Idle,
+                "Only uses the disk when nothing else does",
+            ),
+        ];
+        let current_priority = io_priority_of(p);
+        for (priority, hint) in classes {
+            let current =
+                std::mem::discriminant(&current_priority) == std::mem::discriminant(&priority);
+            if ui
+                .selectable_label(current, priority.to_string())
+                .on_hover_text(hint)
+                .clicked()
+            {
+                self.request(p, Action::SetIoPriority(priority, scope));
+                ui.close_menu();
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label("Level (0 = highest)");
+            ui.add(egui::DragValue::new(&mut self.io_level).clamp_range(IO_LEVEL_RANGE));
+        });
+
+        ui.separator();
+        ui.checkbox(&mut self.all_threads, "Apply to all threads");
+    }
+
+    fn show_oom_menu(&mut self, ui: &mut egui::Ui, p: &ProcessInfo) {
+        ui.label(format!(
+            "Score {}, adjustment {:+}",
+            p.oom_score, p.oom_score_adj
+        ));
+        ui.separator();
+
+        for (label, adj) in OOM_PRESETS {
+            if ui
+                .selectable_label(p.oom_score_adj == adj, format!("{} ({:+})", label, adj))
+                .clicked()
+            {
+                self.request(p, Action::SetOomScoreAdj(adj));
+                ui.close_menu();
+            }
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut self.oom_score_adj)
+                    .clamp_range(OOM_SCORE_ADJ_RANGE)
+                    .speed(10),
+            );
+            if ui.button("Apply").clicked() {
+                self.request(p, Action::SetOomScoreAdj(self.oom_score_adj));
+                ui.close_menu();
+            }
+        });
+    }
+
+    fn thread_scope(&self) -> ThreadScope {
+        if self.all_threads {
+            ThreadScope::AllThreads
+        } else {
+            ThreadScope::MainThread
+        }
+    }
+
+    fn show_affinity_editor(&mut self, ctx: &egui::Context) {
+        let Some(mut editor) = self.affinity_editor.take() else {
+            return;
+        };
+
+        let mut open = true;
+        if let Some(cpus) = editor.show(ctx, &mut open) {
+            let scope = editor
+                .thread
+                .map_or_else(|| self.thread_scope(), ThreadScope::Thread);
+            self.request(&editor.process, Action::SetAffinity(cpus, scope));
+        }
+        if open {
+            self.affinity_editor = Some(editor);
+        }
+    }
+
+    fn show_details(&mut self, ctx: &egui::Context) {
+        let Some(mut details) = self.details.take() else {
+            return;
+        };
+
+        let mut open = true;
+        if let Some((resource, limit)) = details.show(ctx, &mut open) {
+            self.request(&details.process, Action::SetLimit(resource, limit));
+            details.reload();
+        }
+        if open {
+            self.details = Some(details);
+        }
+    }
+
+    /// Run `action` on `p`, first asking for confirmation if it is protected.
+    fn request(&mut self, p: &ProcessInfo, action: Action) {
+        match protection_reason(p.pid) {
+            Some(reason) => {
+                self.pending_override = Some(PendingOverride {
+                    process: p.clone(),
+                    protected: p.pid,
+                    reason,
+                    action,
+                })
+            }
+            None => self.run(p, action, Safety::Enforce),
+        }
+    }
+
+    fn run(&self, p: &ProcessInfo, action: Action, safety: Safety) {
+        let pid = p.pid;
+        match action {
+            Action::Kill(policy) => {
+                self.killing.lock().unwrap().insert(pid);
+                let name = p.name.clone();
+                let killing = self.killing.clone();
+                let notifications = self.notifications.clone();
+
+                tokio::task::spawn_blocking(move || {
+                    let result = kill_pid(pid, &policy, safety);
+                    notifications.push_kill_result(&name, pid, &result);
+                    killing.lock().unwrap().remove(&pid);
+                });
+            }
+            Action::KillGroup(ref policy) => {
+                let (pgid, policy) = (p.pgid, policy.clone());
+                self.kill_in_background(p, &action, format!("process group {}", pgid), move || {
+                    kill_group(pgid, &policy, safety)
+                });
+            }
+            Action::KillSession(ref policy) => {
+                let (sid, policy) = (p.sid, policy.clone());
+                self.kill_in_background(p, &action, format!("session {}", sid), move || {
+                    kill_session(sid, &policy, safety)
+                });
+            }
+            Action::Signal(signal) => match send_signal(pid, signal, safety) {
+                Ok(()) => self.notifications.push(
+                    NotificationKind::Success,
+                    format!("Sent {} to {} ({})", signal, p.name, pid),
+                ),
+                Err(e) => self.notifications.push(
+                    NotificationKind::Error,
+                    format!("Failed to send {} to {} ({}): {}", signal, p.name, pid, e),
+                ),
+            },
+            Action::SignalThread(tid, signal) => {
+                self.report(p, &action, send_thread_signal(pid, tid, signal, safety));
+            }
+            Action::Trash(scope) => {
+                let process = p.clone();
+                let trash = self.trash.clone();
+                self.run_in_background(p, &action, move || {
+                    // Stop outside the lock, which the trash window takes every frame
+                    let item = match scope {
+                        Scope::Process => TrashItem::stop_process(&process, safety),
+                        Scope::Tree => TrashItem::stop_tree(&process, safety),
+                        Scope::Cgroup => {
+                            cgroup_of(pid).and_then(|path| TrashItem::freeze_cgroup(&path, safety))
+                        }
+                    }?;
+                    trash.lock().unwrap().insert(item);
+                    Ok(())
+                });
+            }
+            // Trees, cgroups and all threads can take a while, so these run
+            // in the background
+            Action::Pause(scope) => {
+                self.run_in_background(p, &action, move || match scope {
+                    Scope::Process => suspend_pid(pid, safety),
+                    Scope::Tree => suspend_tree(pid, safety).map(|_| ()),
+                    Scope::Cgroup => cgroup_of(pid).and_then(|path| suspend_cgroup(&path, safety)),
+                });
+            }
+            Action::Resume(scope) => {
+                self.run_in_background(p, &action, move || match scope {
+                    Scope::Process => resume_pid(pid),
+                    Scope::Tree => resume_tree(pid).map(|_| ()),
+                    Scope::Cgroup => cgroup_of(pid).and_then(|path| resume_cgroup(&path)),
+                });
+            }
+            Action::Renice(nice, scope) => {
+                self.run_in_background(p, &action, move || set_nice(pid, nice, scope, safety));
+            }
+            Action::SetPolicy(policy, scope) => {
+                self.run_in_background(p, &action, move || {
+                    set_sched_policy(pid, policy, scope, safety)
+                });
+            }
+            Action::SetAffinity(ref cpus, scope) => {
+                let cpus = cpus.clone();
+                self.run_in_background(p, &action, move || {
+                    set_cpu_affinity(pid, &cpus, scope, safety)
+                });
+            }
+            Action::SetIoPriority(priority, scope) => {
+                self.run_in_background(p, &action, move || {
+                    set_io_priority(pid, priority, scope, safety)
+                });
+            }
+            Action::SetOomScoreAdj(adj) => {
+                self.report(p, &action, set_oom_score_adj(pid, adj, safety));
+            }
+            Action::SetLimit(resource, limit) => {
+                self.report(p, &action, set_limit(pid, resource, limit, safety));
+            }
+        }
+    }
+
+    /// Run a kill of several processes off the UI thread, showing `p` as
+    /// being killed until it is done.
+    fn kill_in_background(
+        &self,
+        p: &ProcessInfo,
+        action: &Action,
+        name: String,
+        kill: impl FnOnce() -> Result<Vec<PidOutcome>, ProcError> + Send + 'static,
+    ) {
+        let pid = p.pid;
+        self.killing.lock().unwrap().insert(pid);
+        let killing = self.killing.clone();
+        let notifications = self.notifications.clone();
+        let refused = self.refusal_slot(p, action);
+
+        tokio::task::spawn_blocking(move || {
+            match kill() {
+                Err(ProcError::Protected(member, reason)) => refused(member, reason),
+                result => notifications.push_group_kill_result(&name, &result),
+            }
+            killing.lock().unwrap().remove(&pid);
+        });
+    }
+
+    /// Run an action on `p` that may block, such as stopping a whole tree,
+    /// off the UI thread and report how it went.
+    fn run_in_background(
+        &self,
+        p: &ProcessInfo,
+        action: &Action,
+        work: impl FnOnce() -> Result<(), ProcError> + Send + 'static,
+    ) {
+        let label = action.label();
+        let name = p.name.clone();
+        let pid = p.pid;
+        let notifications = self.notifications.clone();
+        let refused = self.refusal_slot(p, action);
+
+        tokio::task::spawn_blocking(move || match work() {
+            Err(ProcError::Protected(member, reason)) => refused(member, reason),
+            result => push_report(&notifications, &label, &name, pid, result),
+        });
+    }
+
+    /// Queue `action` on `p` for the override dialog when a background run
+    /// is refused because it reaches a protected process.
+    ///
+    /// Only enforced runs are refused, and they touch nothing before every
+    /// member has been checked, so confirming simply runs the action again.
+    fn refusal_slot(
+        &self,
+        p: &ProcessInfo,
+        action: &Action,
+    ) -> impl FnOnce(i32, ProtectionReason) + Send + 'static {
+        let refused = self.refused.clone();
+        let process = p.clone();
+        let action = action.clone();
+        move |protected, reason| {
+            refused.lock().unwrap().push(PendingOverride {
+                process,
+                protected,
+                reason,
+                action,
+            });
+        }
+    }
+
+    /// Report how a quick, synchronous action on `p` went.
+    fn report(&self, p: &ProcessInfo, action: &Action, result: Result<(), ProcError>) {
+        push_report(&self.notifications, &action.label(), &p.name, p.pid, result);
+    }
+
+    /// Ask before acting on a protected process, making clear what is at stake.
+    fn show_override_dialog(&mut self, ctx: &egui::Context) {
+        if self.pending_override.is_none() {
+            let mut refused = self.refused.lock().unwrap();
+            if !refused.is_empty() {
+                self.pending_override = Some(refused.remove(0));
+            }
+        }
+        let Some(pending) = &self.pending_override else {
+            return;
+        };
+
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("⚠ Protected process")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .stroke(egui::Stroke::new(2.0, egui::Color32::from_rgb(230, 70, 70))),
+            )
+            .show(ctx, |ui| {
+                let warning = if pending.protected == pending.process.pid {
+                    format!(
+                        "{} ({}) is protected because {}.",
+                        pending.process.name, pending.process.pid, pending.reason
+                    )
+                } else {
+                    format!(
+                        "{} of {} ({}) also reaches PID {}, which is protected because {}.",
+                        pending.action.label(),
+                        pending.process.name,
+                        pending.process.pid,
+                        pending.protected,
+                        pending.reason
+                    )
+                };
+                ui.label(egui::RichText::new(warning).strong());
+                ui.label("Acting on it may crash your session or the whole system.");
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let override_button = egui::Button::new(
+                        egui::RichText::new(format!("{} anyway", pending.action.label()))
+                            .color(egui::Color32::WHITE),
+                    )
+                    .fill(egui::Color32::from_rgb(200, 40, 40));
+                    if ui.add(override_button).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            if let Some(pending) = self.pending_override.take() {
+                self.run(&pending.process, pending.action, Safety::Override);
+            }
+        } else if cancelled {
+            self.pending_override = None;
+        }
+    }
+}
+
+/// The process state, marked when the process is paused.
+fn state_text(p: &ProcessInfo) -> String {
+    match p.suspended {
+        Some(_) => format!("{} ⏸", p.state),
+        None => p.state.clone(),
+    }
+}
+
+/// CPU affinity of `p`, read now if the sample left it out.
+fn affinity_of(p: &ProcessInfo) -> Vec<usize> {
+    p.cpu_affinity
+        .clone()
+        .or_else(|| cpu_affinity(p.pid).ok())
+        .unwrap_or_default()
+}
+
+/// I/O priority of `p`, read now if the sample left it out.
+fn io_priority_of(p: &ProcessInfo) -> IoPriority {
+    p.io_priority
+        .or_else(|| io_priority(p.pid).ok())
+        .unwrap_or_default()
+}
+
+/// How long a process has been running, in its two largest units.
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        3600..=86399 => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d {}h", secs / 86400, secs % 86400 / 3600),
+    }
+}
+
+/// Names the single thread an action applies to, if it has one.
+fn thread_suffix(scope: ThreadScope) -> String {
+    match scope {
+        ThreadScope::Thread(tid) => format!(" of thread {}", tid),
+        ThreadScope::MainThread | ThreadScope::AllThreads => String::new(),
+    }
+}
+
+/// Memory size in MB, or "-" if it could not be read.
+pub fn format_memory(bytes: Option<u64>) -> String {
+    match bytes {
+        Some(bytes) => format!("{:.1} MB", bytes as f32 / (1024.0 * 1024.0)),
+        None => "-".to_string(),
+    }
+}
+
+/// Bytes per second in a readable unit, or "-" if it could not be read.
+pub fn format_rate(bytes_per_sec: Option<f64>) -> String {
+    match bytes_per_sec {
+        Some(rate) if rate >= 1024.0 * 1024.0 => format!("{:.1} MB/s", rate / (1024.0 * 1024.0)),
+        Some(rate) if rate >= 1024.0 => format!("{:.1} KB/s", rate / 1024.0),
+        Some(rate) => format!("{:.0} B/s", rate),
+        None => "-".to_string(),
+    }
+}
+
+fn disk_read_rate(p: &ProcessInfo) -> Option<f64> {
+    p.io_rates.map(|rates| rates.read_bytes)
+}
+
+fn disk_write_rate(p: &ProcessInfo) -> Option<f64> {
+    p.io_rates.map(|rates| rates.write_bytes)
+}
+
+fn describe_suspension(suspension: Suspension) -> &'static str {
+    match suspension {
+        Suspension::Stopped => "Paused with SIGSTOP",
+        Suspension::Frozen => "Paused by freezing its cgroup",
+    }
+}
+
+fn sort_header(
+    ui: &mut egui::Ui,
+    title: &str,
     col: SortColumn,
     sort_col: &mut SortColumn,
     descending: &mut bool,
@@ -343,3 +1878,28 @@ fn sort_header(
 fn ord_f32(a: f32, b: f32) -> Ordering {
     a.partial_cmp(&b).unwrap_or(Ordering::Equal)
 }
+
+/// Order rates, with unreadable ones first.
+fn ord_rate(a: Option<f64>, b: Option<f64>) -> Ordering {
+    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+}
+
+/// Notify how `action` on `name` (`pid`) went.
+fn push_report(
+    notifications: &NotificationLog,
+    action: &str,
+    name: &str,
+    pid: i32,
+    result: Result<(), ProcError>,
+) {
+    match result {
+        Ok(()) => notifications.push(
+            NotificationKind::Success,
+            format!("{}: {} ({})", action, name, pid),
+        ),
+        Err(e) => notifications.push(
+            NotificationKind::Error,
+            format!("{} failed for {} ({}): {}", action, name, pid, e),
+        ),
+    }
+}
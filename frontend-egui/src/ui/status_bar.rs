@@ -1,12 +1,18 @@
 //! Status bar component showing process counts and statistics.
 
+use crate::ui::process_table::MemoryMetric;
 use backend::ProcessInfo;
 use eframe::egui;
 
 pub struct StatusBar;
 
 impl StatusBar {
-    pub fn show(ui: &mut egui::Ui, processes: &[ProcessInfo], filtered_count: usize) {
+    pub fn show(
+        ui: &mut egui::Ui,
+        processes: &[ProcessInfo],
+        filtered_count: usize,
+        memory_metric: MemoryMetric,
+    ) {
         ui.separator();
         ui.horizontal(|ui| {
             ui.label(format!("Total processes: {}", processes.len()));
@@ -17,9 +23,21 @@ impl StatusBar {
             }
 
             ui.separator();
-            let total_memory: u64 = processes.iter().map(|p| p.memory_bytes).sum();
+            // Processes whose details could not be read are left out
+            let values: Vec<u64> = processes
+                .iter()
+                .filter_map(|p| memory_metric.value(p))
+                .collect();
+            let total_memory: u64 = values.iter().sum();
+            let counted = if values.len() == processes.len() {
+                String::new()
+            } else {
+                format!(", {} of {} processes", values.len(), processes.len())
+            };
             ui.label(format!(
-                "Total memory: {:.1} GB",
+                "Total memory ({}{}): {:.1} GB",
+                memory_metric.label(),
+                counted,
                 total_memory as f64 / (1024.0 * 1024.0 * 1024.0)
             ));
 
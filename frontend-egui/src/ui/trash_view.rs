@@ -0,0 +1,195 @@
+//! Trash window listing stopped processes that can be restored or killed.
+
+use crate::ui::notifications::{NotificationKind, NotificationLog};
+use backend::{KillPolicy, Trash, TrashItem, TrashTarget};
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub struct TrashView;
+
+impl TrashView {
+    /// Kill items whose retention period has run out. Call once per frame.
+    pub fn empty_expired(
+        ctx: &egui::Context,
+        trash: &Arc<Mutex<Trash>>,
+        policy: &KillPolicy,
+        notifications: &NotificationLog,
+    ) {
+        let (expired, remaining) = {
+            let mut trash = trash.lock().unwrap();
+            (trash.take_expired(), trash.items().len())
+        };
+        if !expired.is_empty() {
+            kill_items(expired, policy, notifications);
+        }
+
+        // Wake up regularly so expiry happens even without user input
+        if remaining > 0 {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+    }
+
+    pub fn show(
+        ctx: &egui::Context,
+        open: &mut bool,
+        trash: &Arc<Mutex<Trash>>,
+        policy: &KillPolicy,
+        notifications: &NotificationLog,
+    ) {
+        egui::Window::new("Trash")
+            .open(open)
+            .default_size(egui::vec2(640.0, 320.0))
+            .show(ctx, |ui| {
+                let mut to_restore = Vec::new();
+                let mut to_kill = Vec::new();
+                let mut empty_all = false;
+
+                {
+                    let mut trash = trash.lock().unwrap();
+
+                    ui.horizontal(|ui| {
+                        ui.label("Kill automatically after");
+                        let mut minutes = trash.retention().as_secs() / 60;
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut minutes)
+                                    .clamp_range(1..=24 * 60)
+                                    .suffix(" min"),
+                            )
+                            .changed()
+                        {
+                            trash.set_retention(Duration::from_secs(minutes * 60));
+                        }
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let empty_button = egui::Button::new("Empty trash")
+                                .fill(egui::Color32::from_rgb(200, 40, 40));
+                            if ui
+                                .add_enabled(!trash.items().is_empty(), empty_button)
+                                .clicked()
+                            {
+                                empty_all = true;
+                            }
+                        });
+                    });
+                    ui.separator();
+
+                    if trash.items().is_empty() {
+                        ui.label("The trash is empty.");
+                        ui.weak("Right-click a process and choose \"Move to trash\" to stop it.");
+                        return;
+                    }
+
+                    let retention = trash.retention();
+                    TableBuilder::new(ui)
+                        .striped(true)
+                        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                        .column(Column::remainder())
+                        .column(Column::exact(140.0))
+                        .column(Column::exact(70.0))
+                        .column(Column::exact(90.0))
+                        .column(Column::exact(90.0))
+                        .column(Column::exact(150.0))
+                        .header(24.0, |mut header| {
+                            for title in ["Name", "Target", "CPU %", "Memory", "Kill in", ""] {
+                                header.col(|ui| {
+                                    ui.strong(title);
+                                });
+                            }
+                        })
+                        .body(|body| {
+                            body.rows(26.0, trash.items().len(), |mut row| {
+                                let item = &trash.items()[row.index()];
+                                row.col(|ui| {
+                                    ui.label(&item.name).on_hover_text(format!(
+                                        "In the trash for {}",
+                                        format_duration(item.age())
+                                    ));
+                                });
+                                row.col(|ui| {
+                                    ui.label(describe_target(&item.target));
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!("{:.1}", item.cpu_percent));
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!(
+                                        "{:.1} MB",
+                                        item.memory_bytes as f32 / (1024.0 * 1024.0)
+                                    ));
+                                });
+                                row.col(|ui| {
+                                    ui.label(format_duration(retention.saturating_sub(item.age())));
+                                });
+                                row.col(|ui| {
+                                    if ui.button("Restore").clicked() {
+                                        to_restore.push(item.id);
+                                    }
+                                    if ui.button("Kill").clicked() {
+                                        to_kill.push(item.id);
+                                    }
+                                });
+                            });
+                        });
+                }
+
+                let mut trash = trash.lock().unwrap();
+                for id in to_restore {
+                    if let Some(item) = trash.take(id) {
+                        let name = item.name.clone();
+                        match item.restore() {
+                            Ok(()) => notifications
+                                .push(NotificationKind::Success, format!("Restored {}", name)),
+                            Err(e) => notifications.push(
+                                NotificationKind::Error,
+                                format!("Failed to restore {}: {}", name, e),
+                            ),
+                        }
+                    }
+                }
+
+                let mut killed: Vec<TrashItem> = to_kill
+                    .into_iter()
+                    .filter_map(|id| trash.take(id))
+                    .collect();
+                if empty_all {
+                    killed.extend(trash.take_all());
+                }
+                if !killed.is_empty() {
+                    kill_items(killed, policy, notifications);
+                }
+            });
+    }
+}
+
+/// Kill trashed items in the background and report each result.
+fn kill_items(items: Vec<TrashItem>, policy: &KillPolicy, notifications: &NotificationLog) {
+    let policy = policy.clone();
+    let notifications = notifications.clone();
+    tokio::task::spawn_blocking(move || {
+        for item in items {
+            let name = item.name.clone();
+            let result = item.kill(&policy);
+            notifications.push_group_kill_result(&name, &result);
+        }
+    });
+}
+
+fn describe_target(target: &TrashTarget) -> String {
+    match target {
+        TrashTarget::Process(pid) => format!("PID {}", pid),
+        TrashTarget::Tree { root, count } => format!("Tree of {} ({} procs)", root, count),
+        TrashTarget::Cgroup(_) => "Cgroup".to_string(),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+    } else {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    }
+}
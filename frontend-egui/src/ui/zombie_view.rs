@@ -0,0 +1,296 @@
+//! Zombies window: parents that do not reap their children, ranked by zombie
+//! count, and process trees orphaned to init or a subreaper.
+
+use crate::ui::notifications::{NotificationKind, NotificationLog};
+use backend::{
+    analyze_zombies, kill_tree, kill_zombie_parent, nudge_zombie_parent, KillPolicy, ProcError,
+    ProtectionReason, Safety, ZombieReport,
+};
+use eframe::egui;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+pub struct ZombieView {
+    /// Last analysis; `None` until the window is first opened.
+    report: Option<Result<ZombieReport, String>>,
+    /// Kills refused because they reach a protected process, to confirm in turn.
+    refused: Arc<Mutex<Vec<PendingOverride>>>,
+    pending_override: Option<PendingOverride>,
+}
+
+/// What a kill from this window targets.
+#[derive(Clone, Copy)]
+enum Target {
+    Parent,
+    Tree,
+}
+
+/// A kill refused over a protected process, waiting for the user to confirm it.
+struct PendingOverride {
+    target: Target,
+    pid: i32,
+    name: String,
+    policy: KillPolicy,
+    protected: i32,
+    reason: ProtectionReason,
+}
+
+/// Kill `pid` or its tree off the UI thread. A refusal over a protected
+/// process goes to `refused` instead of the log, so it can be confirmed.
+fn start_kill(
+    target: Target,
+    pid: i32,
+    name: String,
+    policy: KillPolicy,
+    safety: Safety,
+    notifications: &NotificationLog,
+    refused: &Arc<Mutex<Vec<PendingOverride>>>,
+) {
+    let notifications = notifications.clone();
+    let refused = refused.clone();
+    tokio::task::spawn_blocking(move || {
+        let refusal = match target {
+            Target::Parent => match kill_zombie_parent(pid, &policy, safety) {
+                Err(ProcError::Protected(protected, reason)) => Some((protected, reason)),
+                result => {
+                    notifications.push_kill_result(&name, pid, &result);
+                    None
+                }
+            },
+            Target::Tree => match kill_tree(pid, &policy, safety) {
+                Err(ProcError::Protected(protected, reason)) => Some((protected, reason)),
+                result => {
+                    notifications.push_group_kill_result(&name, &result);
+                    None
+                }
+            },
+        };
+        if let Some((protected, reason)) = refusal {
+            refused.lock().unwrap().push(PendingOverride {
+                target,
+                pid,
+                name,
+                policy,
+                protected,
+                reason,
+            });
+        }
+    });
+}
+
+impl ZombieView {
+    pub fn refresh(&mut self) {
+        self.report = Some(analyze_zombies().map_err(|e| e.to_string()));
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        policy: &KillPolicy,
+        notifications: &NotificationLog,
+    ) {
+        if !*open {
+            // Analyse afresh the next time the window opens
+            self.report = None;
+            return;
+        }
+        if self.report.is_none() {
+            self.refresh();
+        }
+
+        let mut refresh = false;
+        egui::Window::new("Zombies and orphans")
+            .open(open)
+            .default_size(egui::vec2(560.0, 420.0))
+            .show(ctx, |ui| {
+                if ui.button("Refresh").clicked() {
+                    refresh = true;
+                }
+                ui.separator();
+
+                match &self.report {
+                    Some(Ok(report)) => {
+                        refresh |= show_report(ui, report, policy, notifications, &self.refused);
+                    }
+                    Some(Err(e)) => {
+                        ui.colored_label(egui::Color32::from_rgb(230, 70, 70), e.as_str());
+                    }
+                    None => {}
+                }
+            });
+
+        if refresh {
+            self.refresh();
+        }
+        self.show_override_dialog(ctx, notifications);
+    }
+
+    /// Ask before killing through a protected process, as the process table does.
+    fn show_override_dialog(&mut self, ctx: &egui::Context, notifications: &NotificationLog) {
+        if self.pending_override.is_none() {
+            let mut refused = self.refused.lock().unwrap();
+            if !refused.is_empty() {
+                self.pending_override = Some(refused.remove(0));
+            }
+        }
+        let Some(pending) = &self.pending_override else {
+            return;
+        };
+
+        let label = match pending.target {
+            Target::Parent => "Kill parent",
+            Target::Tree => "Kill tree",
+        };
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("⚠ Protected process")
+            .id(egui::Id::new("zombie_override"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .frame(
+                egui::Frame::window(&ctx.style())
+                    .stroke(egui::Stroke::new(2.0, egui::Color32::from_rgb(230, 70, 70))),
+            )
+            .show(ctx, |ui| {
+                let warning = if pending.protected == pending.pid {
+                    format!(
+                        "{} ({}) is protected because {}.",
+                        pending.name, pending.pid, pending.reason
+                    )
+                } else {
+                    format!(
+                        "The tree of {} ({}) includes PID {}, which is protected because {}.",
+                        pending.name, pending.pid, pending.protected, pending.reason
+                    )
+                };
+                ui.label(egui::RichText::new(warning).strong());
+                ui.label("Killing it may crash your session or the whole system.");
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let override_button = egui::Button::new(
+                        egui::RichText::new(format!("{} anyway", label))
+                            .color(egui::Color32::WHITE),
+                    )
+                    .fill(egui::Color32::from_rgb(200, 40, 40));
+                    if ui.add(override_button).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            if let Some(pending) = self.pending_override.take() {
+                start_kill(
+                    pending.target,
+                    pending.pid,
+                    pending.name,
+                    pending.policy,
+                    Safety::Override,
+                    notifications,
+                    &self.refused,
+                );
+            }
+        } else if cancelled {
+            self.pending_override = None;
+        }
+    }
+}
+
+/// Returns whether the report should be refreshed after an action.
+fn show_report(
+    ui: &mut egui::Ui,
+    report: &ZombieReport,
+    policy: &KillPolicy,
+    notifications: &NotificationLog,
+    refused: &Arc<Mutex<Vec<PendingOverride>>>,
+) -> bool {
+    let mut refresh = false;
+
+    ui.strong(format!("Zombies: {}", report.zombie_count()));
+    if report.parents.is_empty() {
+        ui.label("No zombies.");
+    }
+    egui::Grid::new("zombie_parents")
+        .striped(true)
+        .num_columns(3)
+        .show(ui, |ui| {
+            for parent in &report.parents {
+                ui.label(format!("{} ({})", parent.name, parent.pid))
+                    .on_hover_text(format!("Zombie PIDs: {:?}", parent.zombies));
+                ui.label(format!("{} zombies", parent.zombies.len()));
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Send SIGCHLD")
+                        .on_hover_text("Remind the parent to reap its children")
+                        .clicked()
+                    {
+                        match nudge_zombie_parent(parent.pid, Safety::Enforce) {
+                            Ok(()) => notifications.push(
+                                NotificationKind::Success,
+                                format!("Sent SIGCHLD to {} ({})", parent.name, parent.pid),
+                            ),
+                            Err(e) => notifications.push(
+                                NotificationKind::Error,
+                                format!("Failed to signal {} ({}): {}", parent.name, parent.pid, e),
+                            ),
+                        }
+                        refresh = true;
+                    }
+                    if ui
+                        .button("Kill parent")
+                        .on_hover_text("Its zombies are then reaped by init")
+                        .clicked()
+                    {
+                        start_kill(
+                            Target::Parent,
+                            parent.pid,
+                            parent.name.clone(),
+                            policy.clone(),
+                            Safety::Enforce,
+                            notifications,
+                            refused,
+                        );
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+    ui.add_space(8.0);
+    ui.separator();
+    ui.strong(format!("Orphaned trees: {}", report.orphans.len()))
+        .on_hover_text(
+            "Processes whose session ended and that were adopted by init or a subreaper",
+        );
+    egui::Grid::new("orphaned_trees")
+        .striped(true)
+        .num_columns(3)
+        .show(ui, |ui| {
+            for orphan in &report.orphans {
+                ui.label(format!("{} ({})", orphan.name, orphan.root));
+                ui.label(format!(
+                    "{} processes, adopted by {} ({})",
+                    orphan.size, orphan.reaper_name, orphan.reaper
+                ));
+                if ui.button("Kill tree").clicked() {
+                    start_kill(
+                        Target::Tree,
+                        orphan.root,
+                        orphan.name.clone(),
+                        policy.clone(),
+                        Safety::Enforce,
+                        notifications,
+                        refused,
+                    );
+                }
+                ui.end_row();
+            }
+        });
+
+    refresh
+}